@@ -33,8 +33,26 @@ that have a explicit radix (decimal) point. However, mixing explicit radix
 points with fractional notation is disallowed, and the preferred method
 is either just an integer as a numerator or an explicit fraction. If you
 want to express the reciprocal of a number, either input it as 1/n, where n is
-a numeric literal, or as (/ n). Input as numbers with an explicit radix 
-is quite buggy due to the limitations of IEEE754.";
+a numeric literal, or as (/ n). Decimals are exact, so 0.1 is 1/10, and can
+have an exponent, as in 6.022e23 or 1e-9. Integers can be written in other
+bases with the prefixes 0x, 0o and 0b, or in any base up to 36 as 36#zz. See
+(help to-base).
+
+Numbers can also be inexact, meaning that they are IEEE754 floats. Prefix a
+number with #i to make it inexact. Any arithmetic involving an inexact number
+gives an inexact result. See (help exact->inexact).";
+
+    let exact_help =
+"exact->inexact converts an exact number into the nearest float, and
+inexact->exact converts a float into the fraction it represents exactly.
+exact? and inexact? tell them apart. Complex numbers and polynomials only have
+exact parts, so they can't be combined with floats or converted to them.
+
+(+ 0.1 0.2) ; 3/10
+(+ #i0.1 0.2) ; 0.30000000000000004
+(exact->inexact 1/3) ; 0.3333333333333333
+(inexact->exact #i0.5) ; 1/2
+(exact? 1/3) ; true";
 
     let arithmetic_help =
 "The arithmetic operators are +, -, *, /, %, and pow.
@@ -100,15 +118,21 @@ behavior:
 (pow 0 0) ; 1  0^0 is one.
 (pow 0 0 0) ; 0  Problem?
 (pow 2 2 2 2)  ; 65536
-(pow 2 .5)    ; 577/408
+(pow 2 .5)    ; 141421356237309504880168872421/100000000000000000000000000000
 (pow 256 1/8) ; 2
+(pow 27/8 2/3) ; 9/4
 (pow 2 -1)    ; 1/2
-(pow 8 -2)    ; 1/64";
+(pow 8 -2)    ; 1/64
+
+Rational powers are exact when the root is rational. Otherwise the result is
+correct to the number of digits set by set-precision.";
 
     let trig_help =
-"Trigonometric functions. Currently only comprised of sin, cos, and tan. 
-Each function only takes one term, or an expression which is evaluated to
-a single term.";
+"Trigonometric functions: sin, cos, tan, their inverses asin, acos and atan,
+and the hyperbolic sinh, cosh, tanh, asinh, acosh and atanh. The logarithms
+log and ln, and exp, belong with them. Each function only takes one term, or
+an expression which is evaluated to a single term. Results are correct to the
+number of digits set by set-precision, 30 by default.";
 
     let sin_help =
 "The sine function. Takes one term. If no terms are supplied, it evaluates
@@ -117,7 +141,7 @@ express the angle in degrees, convert with (* degrees pi (/ 180)).
 
 (sin pi)  ; 0
 (sin (* 1/2 pi)) ; 1
-(sin (/ (* 2 pi) 3)) ; 7800459734587069/9007199254740992";
+(sin (/ (* 2 pi) 3)) ; 866025403784438646763723170753/1000000000000000000000000000000";
 
     let cos_help =
 "The cosine function. Takes one term. If no terms are supplied, it evaluates
@@ -171,23 +195,399 @@ function.
 (define y (/ x 7)) ;  y = 6;
 (define z (+ (* x y) x)) ; z = 294
 (define (f x) (* x 2 (+ x 2))) ; (f 2) is 16, (f 3) is 30
-(define (h g x) (* (g x) 3 (+ (g x) 2))) ; (h f 4) is 7200";
+(define (h g x) (* (g x) 3 (+ (g x) 2))) ; (h f 4) is 7200
+
+A call that is the last thing a function does, as in a branch of an if, doesn't
+use up any stack, so loops can be written as recursion of any depth.
+
+(define (count n acc) (if (= n 0) acc (count (- n 1) (+ acc 1))))
+(count 100000 0) ; 100000
+
+The constants pi, e, phi, tau, euler-gamma and ln2 cannot be replaced with
+define. Use redefine if you really mean to.
+
+(redefine e 2) ; e = 2";
+
+    let constants_help =
+"The built in constants are computed to the current precision, and are
+recomputed whenever it is changed with set-precision.
+
+pi ; the ratio of a circle's circumference to its diameter
+tau ; 2 pi
+e ; the base of the natural logarithm
+phi ; the golden ratio, (1 + sqrt 5) / 2
+euler-gamma ; the Euler-Mascheroni constant
+ln2 ; the natural logarithm of 2";
 
     let lambda_help =
 "The Anonymous function. (lambda (arguments) (body)).
 (reduce (lambda (x y) (+ x y)) 0 '(1 2 3 4 5)) ; 15
 (define (h g x) (* (g x) 3 (+ (g x) 2)))
-(h (lambda (x) (* x 7)) 4) ; The result is 1080";
+(h (lambda (x) (* x 7)) 4) ; The result is 1080
+A lambda remembers the variables around it when it is made, so it can use
+them after the function that made it has returned.
+(define (make-adder n) (lambda (x) (+ x n)))
+(define add5 (make-adder 5))
+(add5 3) ; 8";
+
+    let let_help =
+"Binds names to values for the length of one expression, without defining them.
+(let ((name value) ...) body). The values of a let are worked out before any of
+the names are bound, those of a let* one after another, so that each can use
+the ones before it, and those of a letrec with all of the names in scope, so
+that procedures can call each other.
+(let ((x 2) (y 3)) (* x y)) ; 6
+(let* ((x 2) (y (* x 10))) (+ x y)) ; 22
+(letrec ((ev (lambda (n) (if (= n 0) true (od (- n 1)))))
+         (od (lambda (n) (if (= n 0) false (ev (- n 1))))))
+  (ev 10)) ; true
+
+A let with a name makes a loop: the name is a procedure of the variables which
+runs the body again with new values.
+(let loop ((i 5) (acc 1)) (if (= i 0) acc (loop (- i 1) (* acc i)))) ; 120";
+
+    let begin_help =
+"(begin expr1 expr2 ...) evaluates each expression in turn, and its value is
+the value of the last one. The body of a lambda, define or let can also be
+several expressions, which are evaluated the same way.
+(set! name value) changes the value of a variable that is already defined, in
+the scope it was defined in, and is an error for one that isn't.
+(define total 0)
+(begin (set! total (+ total 5)) (* total 2)) ; 10
+(define (make-counter) (let ((n 0)) (lambda () (set! n (+ n 1)) n)))
+(define counter (make-counter))
+(counter) ; 1, and 2 the next time";
+
+    let precision_help =
+"Sets the number of decimal digits that transcendental functions such as sin,
+exp and ln are evaluated to. With no terms, returns the current precision.
+
+(set-precision) ; 30
+(set-precision 50)
+(exp 1) ; 2.71828... to 50 places";
+
+    let display_help =
+"Sets how results are written out. The modes are fraction, mixed (3 1/7),
+decimal, scientific, engineering (exponents that are multiples of three), hex,
+octal, binary, base n, which write integers in another base, and auto, the
+default, which writes exact decimals where it can and fractions otherwise.
+Decimal, scientific and engineering take the number of digits to show after
+the point, 10 by default. Repeating digits are put in parentheses,
+and rounded decimals end with an ellipsis. With no terms, returns the current
+mode. rcalc can also be started with --display MODE [DIGITS].
+
+(set-display 'decimal 6)
+(/ 1 7) ; 0.(142857)
+(exp 1) ; 2.718282...
+(set-display 'scientific 3)
+(* 1234 1000) ; 1.234e6
+(set-display 'hex)
+(+ 255) ; 0xff";
+
+    let base_help =
+"Writes an integer in a base from 2 to 36, in the same form that it can be
+typed in. To see every result in another base, use (set-display 'hex),
+'octal, 'binary or (set-display 'base n).
+
+(to-base 255 16) ; 0xff
+(to-base 5 2) ; 0b101
+(to-base 1295 36) ; 36#zz
+(+ 0x10 0b11) ; 19";
+
+    let number_theory_help =
+"Number theory on integers of any size: gcd and lcm take any number of terms,
+(mod-pow b e m) is b^e modulo m, (mod-inv a m) is the inverse of a modulo m,
+(crt residues moduli) solves simultaneous congruences, and totient, divisors,
+(sigma n k), the sum of the kth powers of the divisors of n, and (jacobi a n)
+round them out.
+
+(gcd 12 18 30) ; 6
+(lcm 4 6) ; 12
+(mod-pow 4 13 497) ; 445
+(mod-inv 17 3120) ; 2753
+(crt '(2 3 2) '(3 5 7)) ; 23
+(totient 36) ; 12
+(divisors 12) ; [1, 2, 3, 4, 6, 12]
+(sigma 12) ; 28
+(jacobi 1001 9907) ; -1";
+
+    let primes_help =
+"prime? tests whether an integer is prime. It is exact below 3.3 * 10^24, and
+uses the Baillie-PSW test above that. next-prime and prev-prime find the
+nearest primes, primes-up-to lists them, and factor returns the prime factors
+of a positive integer as a list of (prime exponent) pairs.
+
+(prime? 97) ; true
+(next-prime 100) ; 101
+(prev-prime 100) ; 97
+(primes-up-to 20) ; [2, 3, 5, 7, 11, 13, 17, 19]
+(factor 360) ; [[2, 3], [3, 2], [5, 1]]
+(map (lambda (x) (car x)) (factor 360)) ; [2, 3, 5]";
+
+    let combinatorics_help =
+"Combinatorics with exact answers of any size: factorial, (choose n k),
+(permutations n k), (multinomial k1 k2 ...), the unsigned Stirling numbers of
+the first kind (stirling1 n k) and of the second kind (stirling2 n k), bell,
+catalan, partitions and fib, which also takes negative numbers.
+
+(factorial 20) ; 2432902008176640000
+(choose 52 5) ; 2598960
+(permutations 10 3) ; 720
+(multinomial 2 3 4) ; 1260
+(stirling1 5 2) ; 50
+(stirling2 5 2) ; 15
+(bell 5) ; 52
+(catalan 10) ; 16796
+(partitions 100) ; 190569292
+(fib 100) ; 354224848179261915075";
+
+    let stats_help =
+"Statistics on lists of exact numbers. Most take either a list or any number
+of terms: mean, median, mode (the smallest, if there is a tie), sum, product,
+min, max, the sample variance and stdev, and the population pvariance and
+pstdev. (quantile list p) interpolates between the closest ranks, and
+(z-score x list) uses the population standard deviation. covariance (of a
+sample), correlation and (weighted-mean values weights) take two lists.
+Results are exact unless a square root is irrational, in which case it is
+correct to the number of digits set by set-precision. sum and product also
+run a function over a range of integers; see `series'.
+
+(mean '(1 2 3 4)) ; 5/2
+(median 3 1 4 1 5) ; 3
+(mode '(1 2 2 3)) ; 2
+(variance '(2 4 4 4 5 5 7 9)) ; 32/7
+(pstdev '(2 4 4 4 5 5 7 9)) ; 2
+(quantile '(1 2 3 4 5) 1/4) ; 2
+(z-score 9 '(2 4 4 4 5 5 7 9)) ; 2
+(correlation '(1 2 3) '(2 4 6)) ; 1
+(weighted-mean '(1 2) '(3 1)) ; 5/4";
+
+    let regression_help =
+"Least squares fits, exact for rational data. The points are given as a list
+of xs and a list of ys, or as a matrix with a point in each row, such as one
+made by matrix-from-fn. (linreg xs ys) fits a line and (polyfit xs ys degree)
+a polynomial, and both return a list of the coefficients (the constant term
+first), the residuals and r². linreg-fn and polyfit-fn take the same
+arguments and return the fitted function instead, ready for map or table.
+
+(linreg '(1 2 3) '(2 4 7)) ; [[-2/3, 5/2], [1/6, -1/3, 1/6], 75/76]
+(polyfit '(0 1 2 3) '(1 2 5 10) 2) ; [[1, 0, 1], [0, 0, 0, 0], 1]
+(map (linreg-fn '(1 2 3) '(2 4 6)) '(10)) ; [20]";
+
+    let distributions_help =
+"Probability distributions, each with a density (pmf for the discrete ones,
+pdf for the rest), a cdf and a quantile function, the inverse of the cdf.
+The first argument is the point or, for a quantile, the probability, and the
+rest are the parameters:
+
+normal x [mu sigma]; the standard normal if they are left out
+binomial k trials probability
+poisson k rate
+uniform x low high
+exponential x rate
+student-t x degrees-of-freedom
+chi-squared x degrees-of-freedom
+f x degrees-of-freedom degrees-of-freedom
+
+The binomial and uniform distributions are exact. The rest are correct to the
+number of digits set by set-precision.
+
+(binomial-pmf 2 4 1/2) ; 3/8
+(binomial-cdf 2 4 1/2) ; 11/16
+(uniform-quantile 1/4 0 8) ; 2
+(normal-cdf 1.96) ; 0.9750021048...
+(student-t-cdf 1 1) ; 0.75
+(chi-squared-quantile 0.95 1) ; 3.8414588206...
+
+Adding -sample to a distribution's name draws a random number from it, given
+just the parameters, such as (normal-sample 100 15) or (poisson-sample 4).";
+
+    let random_help =
+"Random numbers. (random) is between 0 and 1 with as many digits as
+set-precision, (random-int a b) is a whole number from a to b inclusive, and
+(random-rational a b denom) is a multiple of 1/denom from a to b. (shuffle list)
+puts a list in random order, and (sample list k) picks k of its items without
+replacement. Each distribution has a sampler too; see `distributions'.
+
+(seed n) restarts the generator so the same numbers come out again, which
+makes simulations reproducible. Until then it is seeded by the system.
+
+(seed 42)
+(random-int 1 6) ; the same roll after every (seed 42)
+(shuffle '(1 2 3 4))
+(sample '(1 2 3 4) 2)";
+
+    let calculus_help =
+"Calculus on functions of one variable, given as a lambda or by name.
+(integrate f a b) is the integral of f from a to b, by tanh-sinh quadrature to
+the current precision. An optional fourth argument sets a looser tolerance,
+and integrate-with-error returns the integral with an estimate of its error.
+Either bound may be infinite, written #iinf or #i-inf, and the integrand may
+blow up at the ends as long as its integral is finite.
+
+(derivative f x) is the derivative of f at x, and (derivative f x n) the nth
+derivative, from finite differences with Richardson extrapolation. (limit f a)
+is the limit of f at a, which may be infinite, and (limit f a 'left) or
+(limit f a 'right) the limit from one side.
+
+(d/dx f 'x) differentiates the body of f symbolically and returns the exact
+derivative as a new procedure. It knows arithmetic, pow and the
+transcendental functions, and treats any other symbol as a constant. The
+variable can be left out when f has only one parameter.
+
+(integrate (lambda (x) (* x x)) 0 1) ; 1/3
+(integrate (lambda (x) (/ 1 (+ 1 (* x x)))) #i-inf #iinf) ; pi
+(integrate-with-error (lambda (x) (exp (- x))) 0 #iinf 1e-10)
+(derivative (lambda (x) (* x x x)) 2) ; 12
+(derivative (lambda (x) (* x x x)) 2 2) ; 12
+(limit (lambda (x) (/ (sin x) x)) 0) ; 1
+(limit (lambda (x) (/ x (+ x 1))) #iinf) ; 1
+(limit (lambda (x) (/ (abs x) x)) 0 'right) ; 1
+(d/dx (lambda (x) (* x (sin x))) 'x) ; (lambda (x) (+ (sin x) (* x (cos x))))";
+
+    let solve_help =
+"Roots and minima of functions of one variable, given as a lambda or by name.
+(find-root f a b) finds where f is zero between a and b by bisection, and f(a)
+and f(b) must have opposite signs. (newton f x) starts Newton's method at x,
+which is faster but can wander off or fail. (minimize f a b) is where f is
+least between a and b, by golden section search. Each takes a tolerance as an
+optional last argument, and otherwise works to the current precision. A root
+that is found exactly, such as 2 for x^2 - 4, is returned exactly.
+
+(find-root (lambda (x) (- (* x x) 2)) 0 2) ; the square root of 2
+(newton (lambda (x) (- (* x x) 4)) 1) ; 2
+(minimize (lambda (x) (* (- x 1) (- x 1))) 0 3 1e-6) ; about 1";
+
+    let series_help =
+"(sum f a b) adds up f(k) for each integer k from a to b, and (product f a b),
+or prod, multiplies them, without building a list of the range. (series f a)
+is the infinite sum of f(k) from k = a on. Its partial sums are extrapolated
+to infinitely many terms, so slowly converging series such as 1/k^2 and
+alternating series need only a few thousand terms, and it fails if the sum
+doesn't settle down within the current precision.
+
+(sum (lambda (k) (* k k)) 1 10) ; 385
+(prod (lambda (k) k) 1 5) ; 120
+(series (lambda (k) (/ 1 (* k k))) 1) ; pi^2 / 6
+(series (lambda (k) (/ (pow -1 k) (+ (* 2 k) 1))) 0) ; pi / 4";
+
+    let polynomial_help =
+"Polynomials in x with exact rational coefficients. (poly coeffs) builds one
+from a list of coefficients, constant term first as polyfit gives them, and
+poly-coeffs takes it apart again. +, -, * and / work on polynomials and
+numbers, where / is the quotient of polynomial division and % the remainder;
+poly-div returns both. poly-eval evaluates a polynomial at a number, or at
+another polynomial to compose them. poly-gcd, poly-deriv, poly-integral and
+poly-degree do what they say. poly-roots lists the distinct real roots in
+order, exactly when they are rational and to the current precision when not.
+matrix-char-poly gives the characteristic polynomial det(xI - A) of a matrix.
+
+(poly '(-1 2 3)) ; 3x^2 + 2x - 1
+(* (poly '(1 1)) (poly '(-1 1))) ; x^2 - 1
+(poly-div (poly '(-1 0 1)) (poly '(-1 1))) ; [x + 1, 0]
+(poly-eval (poly '(-1 2 3)) 2) ; 15
+(poly-roots (poly '(-1 2 3))) ; [-1, 1/3]
+(poly-roots (poly '(-2 0 1))) ; [-1.414..., 1.414...]
+(matrix-char-poly (make-matrix (list (list 2 1) (list 1 2)))) ; x^2 - 4x + 3";
+
+    let complex_help =
+"Complex numbers are written with exact rational parts, such as 3+4i, -1/2i
+or 2-1i. Arithmetic, pow, =, and the trigonometric functions all accept them,
+and roots and logarithms of negative numbers return them. re, im, conj, abs
+and arg take a number apart.
+
+(* 3+4i 3-4i) ; 25
+(pow -4 1/2) ; 2i
+(re 3+4i) ; 3
+(im 3+4i) ; 4
+(conj 3+4i) ; 3-4i
+(abs 3+4i) ; 5
+(arg -1) ; pi
+(ln -1) ; pi times i, to the current precision";
 
     let mut help_map: HashMap<String, String> = HashMap::new();
 
     for (key, val) in ["help", "use", "arithmetic", "+", "-", "*", "/", "pow", "sin",
                        "cos", "tan", "trig", "<", "<=", "=", ">=", ">", "if", "logic",
-                       "define", "lambda", "≤", "≥"]
+                       "define", "lambda", "let", "let*", "letrec", "begin", "set!",
+                       "set-precision", "constants", "redefine",
+                       "complex", "re", "im", "conj", "abs", "arg", "exact->inexact",
+                       "inexact->exact", "exact?", "inexact?", "set-display", "to-base",
+                       "gcd", "lcm", "mod-pow", "mod-inv", "crt", "totient", "divisors",
+                       "sigma", "jacobi",
+                       "prime?", "next-prime", "prev-prime", "primes-up-to", "factor",
+                       "factorial", "choose", "permutations", "multinomial", "stirling1",
+                       "stirling2", "bell", "catalan", "partitions", "fib",
+                       "statistics", "stats", "mean", "median", "mode", "variance",
+                       "pvariance", "stdev", "pstdev", "quantile", "min", "max", "sum",
+                       "product", "covariance", "correlation", "z-score", "weighted-mean",
+                       "regression", "linreg", "polyfit", "linreg-fn", "polyfit-fn",
+                       "distributions", "probability", "normal-pdf", "normal-cdf",
+                       "normal-quantile", "binomial-pmf", "binomial-cdf", "binomial-quantile",
+                       "poisson-pmf", "poisson-cdf", "poisson-quantile", "uniform-pdf",
+                       "uniform-cdf", "uniform-quantile", "exponential-pdf", "exponential-cdf",
+                       "exponential-quantile", "student-t-pdf", "student-t-cdf",
+                       "student-t-quantile", "chi-squared-pdf", "chi-squared-cdf",
+                       "chi-squared-quantile", "f-pdf", "f-cdf", "f-quantile",
+                       "random", "random-int", "random-rational", "shuffle", "sample", "seed",
+                       "calculus", "integrate", "integrate-with-error", "derivative", "limit",
+                       "d/dx",
+                       "solve", "find-root", "newton", "minimize", "series", "prod",
+                       "polynomial", "poly", "poly-coeffs", "poly-eval", "poly-div",
+                       "poly-gcd", "poly-deriv", "poly-integral", "poly-degree", "poly-roots",
+                       "matrix-char-poly",
+                       "≤", "≥"]
         .iter().zip([help_help, use_help, arithmetic_help, add_help, sub_help, mul_help,
                      div_help, pow_help, sin_help, cos_help, tan_help, trig_help,
                      lt_help, lte_help.clone(), eq_help, gte_help.clone(), gt_help,
-                     condit_help, logic_help, define_help, lambda_help, 
+                     condit_help, logic_help, define_help.clone(), lambda_help, 
+                     let_help, let_help.clone(), let_help.clone(), begin_help,
+                     begin_help.clone(),
+                     precision_help, constants_help, define_help,
+                     complex_help, complex_help.clone(), complex_help.clone(),
+                     complex_help.clone(), complex_help.clone(), complex_help.clone(),
+                     exact_help, exact_help.clone(), exact_help.clone(), exact_help.clone(),
+                     display_help, base_help, number_theory_help, number_theory_help.clone(),
+                     number_theory_help.clone(), number_theory_help.clone(),
+                     number_theory_help.clone(), number_theory_help.clone(),
+                     number_theory_help.clone(), number_theory_help.clone(),
+                     number_theory_help.clone(), primes_help, primes_help.clone(),
+                     primes_help.clone(), primes_help.clone(), primes_help.clone(),
+                     combinatorics_help, combinatorics_help.clone(), combinatorics_help.clone(),
+                     combinatorics_help.clone(), combinatorics_help.clone(),
+                     combinatorics_help.clone(), combinatorics_help.clone(),
+                     combinatorics_help.clone(), combinatorics_help.clone(),
+                     combinatorics_help.clone(), stats_help, stats_help.clone(),
+                     stats_help.clone(), stats_help.clone(), stats_help.clone(),
+                     stats_help.clone(), stats_help.clone(), stats_help.clone(),
+                     stats_help.clone(), stats_help.clone(), stats_help.clone(),
+                     stats_help.clone(), stats_help.clone(), stats_help.clone(),
+                     stats_help.clone(), stats_help.clone(), stats_help.clone(),
+                     stats_help.clone(), regression_help, regression_help.clone(),
+                     regression_help.clone(), regression_help.clone(),
+                     regression_help.clone(),
+                     distributions_help, distributions_help.clone(),
+                     distributions_help.clone(), distributions_help.clone(),
+                     distributions_help.clone(), distributions_help.clone(),
+                     distributions_help.clone(), distributions_help.clone(),
+                     distributions_help.clone(), distributions_help.clone(),
+                     distributions_help.clone(), distributions_help.clone(),
+                     distributions_help.clone(), distributions_help.clone(),
+                     distributions_help.clone(), distributions_help.clone(),
+                     distributions_help.clone(), distributions_help.clone(),
+                     distributions_help.clone(), distributions_help.clone(),
+                     distributions_help.clone(), distributions_help.clone(),
+                     distributions_help.clone(), distributions_help.clone(),
+                     distributions_help.clone(), distributions_help.clone(), random_help,
+                     random_help.clone(), random_help.clone(), random_help.clone(),
+                     random_help.clone(), random_help.clone(), calculus_help,
+                     calculus_help.clone(), calculus_help.clone(), calculus_help.clone(),
+                     calculus_help.clone(), calculus_help.clone(), solve_help, solve_help.clone(),
+                     solve_help.clone(), solve_help.clone(), series_help,
+                     series_help.clone(), polynomial_help, polynomial_help.clone(),
+                     polynomial_help.clone(), polynomial_help.clone(), polynomial_help.clone(),
+                     polynomial_help.clone(), polynomial_help.clone(), polynomial_help.clone(),
+                     polynomial_help.clone(), polynomial_help.clone(), polynomial_help.clone(),
                      lte_help, gte_help].iter())
     {
             help_map.insert(key.to_string(), val.to_string());
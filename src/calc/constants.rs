@@ -0,0 +1,40 @@
+//! Mathematical constants, computed to the current precision.
+
+use std::num;
+use super::{Environment, BigNum, BigRational};
+use super::operator::fixed;
+
+pub static CONSTANTS: [&'static str, ..6] = ["pi", "e", "phi", "tau", "euler-gamma", "ln2"];
+
+/// The value of a named constant, correct to `digits` decimal places.
+pub fn constant(name: &str, digits: uint) -> Option<BigRational> {
+    let p = fixed::bits_for(digits);
+
+    let value = match name {
+        "pi" => fixed::pi(p),
+        "tau" => fixed::pi(p) << 1,
+        "e" => fixed::exp(&num::one(), p).unwrap(),
+        "phi" => (fixed::one(p) + fixed::isqrt(&(fixed::big(5) << 2 * p))) >> 1,
+        "euler-gamma" => fixed::euler_gamma(p),
+        "ln2" => fixed::ln2(p),
+        _ => return None
+    };
+
+    Some(fixed::to_rational(&value, p, digits))
+}
+
+/// Binds the constants in the global frame of an environment at its current
+/// precision. Constants the user has replaced with `redefine' are left alone.
+pub fn prelude(env: &mut Environment) {
+    let mut env = Environment { frame: env.global(), precision: env.precision,
+                                display: env.display, rng: env.rng.clone() };
+    for name in CONSTANTS.iter() {
+        let name = name.to_string();
+        if env.lookup(&name).is_ok() && !env.is_constant(&name) {
+            continue
+        }
+
+        let value = constant(name.as_slice(), env.precision).unwrap();
+        env.bind_constant(name, BigNum(value));
+    }
+}
@@ -1,26 +1,117 @@
-//! Evaluate functions defined by the user
+//! Evaluate functions defined by the user. Calls and conditionals in tail
+//! position are evaluated in a loop rather than by recursion, so that a tail
+//! recursive function runs in constant stack space however deep it goes.
 
-use super::{CalcResult, Environment, Evaluate, ArgType, Atom, Proc, BadNumberOfArgs};
+use super::{CalcResult, Environment, Evaluate, ArgType, Atom, SExpr, Expression, BuiltIn,
+            Function, LiteralType, Proc, Symbol, BadNumberOfArgs};
+use super::types::operator::{Logic, If, Let, LetStar, LetRec, Begin};
+use super::operator;
+use super::operator::logic::branch;
 
-///Returns the value of the function for the arguments given
-pub fn eval(fn_name: &String, args: &Vec<ArgType>,
-            env: &mut Environment) -> CalcResult {
-    
+/// What is left to do after one step of evaluating an expression.
+enum Step {
+    /// Nothing; this is the value of the expression.
+    Value(ArgType),
+    /// Evaluate this expression, whose value is the value of the whole.
+    Tail(Expression),
+    /// Evaluate the body of a procedure or a `let' in the frame it binds.
+    Call(Expression, Environment),
+}
+
+/// Evaluates an expression, replacing it with the expression in tail position
+/// until there is a value. Only the frame of the latest call is kept.
+pub fn eval(expr: &Expression, env: &mut Environment) -> CalcResult {
+    let mut tail: Option<Expression> = None;
+    let mut frame: Option<Environment> = None;
+
+    loop {
+        let next = {
+            let expr = match tail {
+                Some(ref x) => x,
+                None => expr
+            };
+            let env = match frame {
+                Some(ref mut x) => x,
+                None => &mut *env
+            };
+            try!(step(expr, env))
+        };
+
+        match next {
+            Value(x) => return Ok(x),
+            Tail(x) => tail = Some(x),
+            Call(x, child_env) => {
+                tail = Some(x);
+                frame = Some(child_env);
+            }
+        }
+    }
+}
+
+fn step(expr: &Expression, env: &mut Environment) -> CalcResult<Step> {
+    match expr.expr_type {
+        BuiltIn(Logic(If)) => tail_step(try!(branch(&expr.args, env)), env),
+        BuiltIn(Begin) => tail_step(try!(super::sequence(&expr.args, env)), env),
+        BuiltIn(op @ Let) | BuiltIn(op @ LetStar) | BuiltIn(op @ LetRec) => {
+            let (body, mut frame) = try!(super::let_frame(&expr.args, env, op));
+            match body {
+                SExpr(x) => Ok(Call(x, frame)),
+                x => Ok(Value(Atom(try!(resolve(&x, &mut frame)))))
+            }
+        },
+        BuiltIn(op) => Ok(Value(try!(operator::eval(op, &expr.args, env)))),
+        Function(ref f) => call(f, &expr.args, env)
+    }
+}
+
+/// An expression in tail position, or the value of an atom there.
+fn tail_step(arg: ArgType, env: &mut Environment) -> CalcResult<Step> {
+    match arg {
+        SExpr(x) => Ok(Tail(x)),
+        x => Ok(Value(Atom(try!(resolve(&x, env)))))
+    }
+}
+
+/// Binds the arguments of a call to a procedure in a new frame. A name that
+/// isn't bound to a procedure just gives its value.
+fn call(fn_name: &String, args: &Vec<ArgType>, env: &mut Environment) -> CalcResult<Step> {
     let value = try!(env.lookup(fn_name));
 
-    let (args_to_fulfill, func) = match value {
-        Proc(x, y) => (x, y),
-        _ => return Ok(Atom(value)),
+    let (args_to_fulfill, func, scope) = match value {
+        Proc(x, y, z) => (x, y, z),
+        _ => return Ok(Value(Atom(value))),
     };
 
     if args.len() != args_to_fulfill.len() {
         return Err(BadNumberOfArgs(fn_name.clone(), "only".to_string(), args_to_fulfill.len()))
     }
 
-    let mut child_env = Environment::new_frame(env);
+    let mut child_env = env.new_call_frame(&scope);
     for (arg, val) in args_to_fulfill.iter().zip(args.iter()) {
-        child_env.symbols.insert(arg.clone(), try!(val.arg_to_literal(env)));
+        child_env.bind(arg.clone(), try!(resolve(val, env)));
     }
 
-    func.eval(&mut child_env)
+    Ok(Call(func, child_env))
+}
+
+/// The value of an argument, with variables looked up in the frame they are
+/// used in, since the procedure they are handed to can't see that frame. A
+/// symbol that isn't bound to anything is quoted, and is a value of its own.
+pub fn resolve(arg: &ArgType, env: &mut Environment) -> CalcResult<LiteralType> {
+    match *arg {
+        Atom(Symbol(ref x)) => match env.lookup(x) {
+            Ok(val) => Ok(val),
+            Err(_) => Ok(Symbol(x.clone()))
+        },
+        _ => arg.arg_to_literal(env)
+    }
+}
+
+/// The value of the body of a `let' or the last expression of a `begin',
+/// which may be a lone atom.
+pub fn value_of(arg: &ArgType, env: &mut Environment) -> CalcResult {
+    match *arg {
+        SExpr(ref x) => x.eval(env),
+        _ => Ok(Atom(try!(resolve(arg, env))))
+    }
 }
@@ -7,8 +7,11 @@ use self::matrix::{Matrice, MatrixErrors, BadDimensionality};
 use self::types::MatrixErr;
 use self::types::operator::{MatrixOps, MakeMatrix, MatrixSetRow, MatrixSetCol, 
                             MatrixAppendRows, Determ, MatrixInv, MatrixAppendCols,
-                            MatrixGetElem, MatrixGetRow, MatrixGetCol, MatrixFromFn};
-use super::{ArgType, Atom, CalcResult, Environment, Evaluate};
+                            MatrixGetElem, MatrixGetRow, MatrixGetCol, MatrixFromFn,
+                            MatrixCharPoly};
+use self::types::literal::poly;
+use std::num;
+use super::{ArgType, Atom, CalcResult, Environment, Evaluate, BigRational, Ratio};
 use super::{BadArgType, BadNumberOfArgs};
 use super::{Lit, List, BigNum, Matrix, Symbol};
 
@@ -22,7 +25,7 @@ pub fn matrix_ops(args: &Args, env: &mut Env, mop: MatrixOps) -> CalcResult {
         MatrixAppendRows | MatrixAppendCols => matrix_append(args, env, mop),
         MatrixGetElem => get_elem(args, env),
         MatrixGetRow | MatrixGetCol => get_row_col(args,env, mop),
-        Determ | MatrixInv => single(args, env, mop),
+        Determ | MatrixInv | MatrixCharPoly => single(args, env, mop),
         MatrixFromFn => matrix_from_fn(args, env),
     }
 }        
@@ -53,7 +56,7 @@ pub fn matrix_from_fn(args: &Args, env: &mut Env) -> CalcResult {
                                    "at least".to_string(), 2))
     }
 
-    let (names, func) = try!(proc_getter(args, env));
+    let (names, func, scope) = try!(proc_getter(args, env));
     if names.len() < 1 {
         return Err(BadArgType("At least one variable must be supplied".to_string()))
     }
@@ -78,13 +81,13 @@ pub fn matrix_from_fn(args: &Args, env: &mut Env) -> CalcResult {
     let mut matrix_vec: Vec<Lit> = Vec::new();
 
     for column in range(0, lists[0].len()) {
-        let mut child_env = Environment::new_frame(env);
+        let mut child_env = env.new_call_frame(&scope);
 
         let values: Vec<Lit> = lists.iter().map(|x| x[column].clone()).collect();
         matrix_vec.push_all(values.as_slice());
 
         for (arg, val) in names.iter().zip(values.iter()) {
-            child_env.symbols.insert(arg.clone(), val.clone());
+            child_env.bind(arg.clone(), val.clone());
         }
 
         matrix_vec.push(try!(try!(func.eval(&mut child_env)).desymbolize(env)));
@@ -280,6 +283,44 @@ pub fn single(args: &Args, env: &mut Env, mop: MatrixOps) -> CalcResult {
             Some(x) => Ok(Atom(Matrix(x))),
             None =>  Err(BadArgType("No determinant for this matrix".to_string()))
         },
+        MatrixCharPoly => char_poly(&matrix),
         _ => fail!("Undefined!")
     }
 }
+
+/// The characteristic polynomial det(xI - A) of a square matrix of exact
+/// numbers, by the Faddeev-LeVerrier recurrence: starting from M = I, each
+/// coefficient c_(n-k) is -tr(AM) / k, and then M becomes AM + c_(n-k) I.
+pub fn char_poly(matrix: &Matrice<Lit>) -> CalcResult {
+    let n = matrix.rows();
+    if n != matrix.cols() {
+        return Err(BadArgType("Only square matrices have characteristic polynomials".to_string()))
+    }
+
+    let mut elems: Vec<BigRational> = Vec::new();
+    for x in matrix.to_vec().move_iter() {
+        match x {
+            BigNum(y) => elems.push(y),
+            y => return Err(BadArgType(format!("`matrix-char-poly' needs exact numbers, not {}",
+                                               y)))
+        }
+    }
+
+    let a = match Matrice::from_vec(elems, n, n) {
+        Ok(x) => x,
+        Err(m) => return Err(MatrixErr(m))
+    };
+    let ident: Matrice<BigRational> = Matrice::ident(n);
+    let mut coeffs: Vec<BigRational> = Vec::from_elem(n + 1, num::one());
+    let mut m = ident.clone();
+
+    for k in range(1, n + 1) {
+        let am = a * m;
+        let trace = am.left_diag().fold(num::zero::<BigRational>(), |acc, x| acc + *x);
+        let c = -trace / Ratio::from_integer(num::from_uint(k).unwrap());
+        m = am + ident.scalar(&c, |x, y| *x * *y);
+        *coeffs.get_mut(n - k) = c;
+    }
+
+    Ok(Atom(poly(coeffs)))
+}
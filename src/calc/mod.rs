@@ -6,14 +6,16 @@ extern crate parse;
 
 pub use self::num::rational::{BigRational, Ratio};
 pub use self::num::bigint;
-pub use self::types::{CalcResult, Environment, 
+pub use self::types::{CalcResult, Environment, Scope, Global,
                       ErrorKind, BadArgType, BadNumberOfArgs, 
-                      BadPowerRange, BadFloatRange, NonBoolean,
+                      BadPowerRange, BadFloatRange, NonBoolean, DivByZero, NoConvergence,
                       };
 pub use self::types::sexpr::{Atom, SExpr, Expression, ArgType, BuiltIn, Function};
 pub use self::types::literal::{Lit, LitRes, LiteralType, 
-                               BigNum, Boolean, List, Matrix, Proc, Symbol, Void};
+                               BigNum, Float, Complex, Boolean, List, Matrix, Proc, Symbol,
+                               Void};
 pub use self::common::help;
+use self::types::operator::{OperatorType, Let, Begin};
 pub use self::literal::{cons, car, cdr, list};
 
 pub mod matrice;
@@ -22,21 +24,26 @@ pub mod operator;
 pub mod function;
 pub mod common;
 pub mod pretty;
+pub mod constants;
 
 /// A structure to allow persistence of variables and functions
 
-pub fn define(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
+/// Binds a name in the current environment. Constants can only be replaced
+/// when `force` is set, as it is for `redefine'.
+pub fn define(args: &Vec<ArgType>, env: &mut Environment, force: bool) -> CalcResult {
     if args.len() < 2 {
         return Err(BadNumberOfArgs("define".to_string(), "only".to_string(), 2))
     }
     
-    let name_and_vars = match try!(args[0].desymbolize(env)) {
-        List(ref x) => if x.len() == 0 {
+    // `(define (f) ...)' makes a procedure even though it has no parameters
+    let (name_and_vars, is_proc) = match args[0] {
+        Atom(Symbol(ref x)) => (vec![Symbol(x.clone())], false),
+        Atom(List(ref x)) => if x.len() == 0 {
             return Err(BadArgType("Name required for definitions".to_string()))
         } else {
-            x.clone()
+            (x.clone(), true)
         },
-        x => return Err(BadArgType(format!("{} is not a symbol", x)))
+        ref x => return Err(BadArgType(format!("{} is not a symbol", x)))
     };
 
     let name = match name_and_vars[0] {
@@ -44,6 +51,14 @@ pub fn define(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
         _ => return Err(BadArgType("Names can only be symbols!".to_string()))
     };
 
+    if env.is_constant(&name) {
+        if !force {
+            return Err(BadArgType(format!("`{}' is a constant. Use `redefine' to replace it",
+                                          name)))
+        }
+        env.unmark_constant(&name);
+    }
+
     let vars = if name_and_vars.len() == 1 {
         vec![]
     } else {
@@ -57,40 +72,217 @@ pub fn define(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
         string_vec
     };
 
-    if args.len() == 2 {
-        let arg = args.last().unwrap();
-        match arg {
-            &Atom(ref x) => {
-                env.symbols.insert(name, x.clone());
-                return Ok(Atom(Void))
-            }
-            &SExpr(ref x) => {
-                match arg.eval(env) {
-                    Ok(res) => { 
-                        env.symbols.insert(name, match res {
-                            Atom(y) => y,
-                            _ => fail!("Impossible!")
-                        });
-                    }
-                    Err(_) => { env.symbols.insert(name, Proc(vars, x.clone())); }
-                }
-                return Ok(Atom(Void))
+    // several expressions are evaluated in order, as in `begin'
+    let body = if args.len() == 2 {
+        args[1].clone()
+    } else {
+        SExpr(Expression::new(BuiltIn(Begin), args.slice_from(1).to_vec()))
+    };
+
+    // the body of a procedure is only evaluated when it is called
+    if is_proc {
+        let body = match body {
+            SExpr(x) => x,
+            x => Expression::new(BuiltIn(Begin), vec![x])
+        };
+        let scope = Scope::of(env);
+        env.bind(name, Proc(vars, body, scope));
+        return Ok(Atom(Void))
+    }
+
+    match body {
+        Atom(x) => env.bind(name, x),
+        SExpr(x) => match x.eval(env) {
+            Ok(Atom(y)) => env.bind(name, y),
+            Ok(_) => fail!("Impossible!"),
+            Err(_) => {
+                let scope = Scope::of(env);
+                env.bind(name, Proc(vars, x, scope));
             }
         }
     }
+    Ok(Atom(Void))
+}
 
-    //there's multiple expressions involved, so we just pack them all that way
-    match args.last().unwrap() {
-        &Atom(ref x) => {
-            env.symbols.insert(name, x.clone());
-            Ok(Atom(Void))
-        }
-        &SExpr(ref x) => {
-            let expr = Expression::new(x.expr_type.clone(), args.tail().to_owned());
-            env.symbols.insert(name, Proc(vars, expr));
-            Ok(Atom(Void))
+/// Makes the procedure a lambda expression holds, closing over the environment
+/// the lambda is evaluated in.
+pub fn lambda(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
+    if args.len() == 1 {
+        match args[0] {
+            Atom(Proc(ref vars, ref body, _)) => {
+                return Ok(Atom(Proc(vars.clone(), body.clone(), Scope::of(env))))
+            },
+            _ => { }
         }
     }
+    Err(BadArgType("Malformed lambda".to_string()))
+}
+
+/// The body of a `let', `let*' or `letrec', and the new frame it is evaluated
+/// in. A `let' evaluates its values outside of the frame, a `let*' in it one
+/// after another, and a `letrec' in it too, so that procedures bound by it can
+/// call each other. A named `let' binds its name, in a frame of its own, to a
+/// procedure of the variables with the body as its body, and calls it.
+pub fn let_frame(args: &Vec<ArgType>, env: &mut Environment,
+                 op: OperatorType) -> CalcResult<(ArgType, Environment)> {
+    let malformed = || BadArgType(format!("Malformed {}", op));
+
+    let (name, rest) = match args.as_slice().head() {
+        Some(&Atom(Symbol(ref x))) => (Some(x.clone()), args.slice_from(1)),
+        _ => (None, args.as_slice())
+    };
+
+    let mut vars = Vec::new();
+    match rest.head() {
+        Some(&Atom(List(ref x))) => for var in x.iter() {
+            match *var {
+                Symbol(ref v) => vars.push(v.clone()),
+                _ => return Err(malformed())
+            }
+        },
+        _ => return Err(malformed())
+    }
+    if rest.len() != vars.len() + 2 {
+        return Err(malformed())
+    }
+
+    let values = rest.slice(1, rest.len() - 1);
+    let body = rest[rest.len() - 1].clone();
+    let mut frame = Environment::new_frame(env);
+
+    match name {
+        Some(name) => {
+            let func = match body {
+                SExpr(ref x) => x.clone(),
+                ref x => Expression::new(BuiltIn(Begin), vec![x.clone()])
+            };
+            // the frame only holds the procedure's scope weakly, as it would
+            // otherwise keep itself alive; see `Scope'
+            let scope = Scope::of(&frame);
+            frame.bind(name, Proc(vars.clone(), func, scope.clone()));
+
+            let mut call_env = frame.new_call_frame(&scope);
+            for (var, val) in vars.iter().zip(values.iter()) {
+                call_env.bind(var.clone(), try!(function::resolve(val, env)));
+            }
+            return Ok((body, call_env))
+        },
+        None => { }
+    }
+
+    for (var, val) in vars.iter().zip(values.iter()) {
+        let value = if op == Let {
+            try!(function::resolve(val, env))
+        } else {
+            try!(function::resolve(val, &mut frame))
+        };
+        frame.bind(var.clone(), value);
+    }
+
+    Ok((body, frame))
+}
+
+/// Evaluates a `let' form outside of tail position.
+pub fn let_eval(args: &Vec<ArgType>, env: &mut Environment, op: OperatorType) -> CalcResult {
+    let (body, mut frame) = try!(let_frame(args, env, op));
+    function::value_of(&body, &mut frame)
+}
+
+/// Evaluates all but the last expression of a `begin', for what they do, and
+/// gives back the last one, which is in tail position. An empty `begin' has no
+/// value.
+pub fn sequence(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult<ArgType> {
+    if args.len() == 0 {
+        return Ok(Atom(Void))
+    }
+
+    for arg in args.slice_to(args.len() - 1).iter() {
+        try!(arg.eval(env));
+    }
+    Ok(args[args.len() - 1].clone())
+}
+
+/// Evaluates a `begin' form outside of tail position.
+pub fn begin(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
+    let last = try!(sequence(args, env));
+    function::value_of(&last, env)
+}
+
+/// Changes the value of a variable in the frame it is bound in. Unlike
+/// `define', it never makes a new binding.
+pub fn set(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
+    if args.len() != 2 {
+        return Err(BadNumberOfArgs("set!".to_string(), "only".to_string(), 2))
+    }
+
+    let name = match args[0] {
+        Atom(Symbol(ref x)) => x.clone(),
+        _ => return Err(BadArgType("`set!' needs the name of a variable".to_string()))
+    };
+
+    // only the nearest binding matters, so a local variable may hide a constant
+    let value = try!(function::resolve(&args[1], env));
+    if !try!(env.set(&name, value)) {
+        return Err(BadArgType(format!("`{}' is a constant. Use `redefine' to replace it",
+                                      name)))
+    }
+    Ok(Atom(Void))
+}
+
+/// Sets the number of digits transcendental functions are evaluated to. With no
+/// arguments, returns the current precision.
+pub fn set_precision(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
+    use self::bigint::ToBigInt;
+    use self::matrice::arg_to_uint;
+
+    if args.len() > 1 {
+        return Err(BadNumberOfArgs("set-precision".to_string(), "at most".to_string(), 1))
+    }
+
+    if args.len() == 0 {
+        return Ok(Atom(BigNum(Ratio::from_integer(env.precision.to_bigint().unwrap()))))
+    }
+
+    let digits = try!(arg_to_uint(try!(args[0].desymbolize(env))));
+    if digits == 0 {
+        return Err(BadArgType("Precision must be at least one digit".to_string()))
+    }
+
+    env.precision = digits;
+    constants::prelude(env);
+    Ok(Atom(Void))
+}
+
+/// Sets how results are written out, e.g. (set-display 'decimal 20). With no
+/// arguments, returns the current display mode.
+pub fn set_display(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
+    use self::types::{DisplayMode, DEFAULT_DISPLAY_DIGITS};
+    use self::matrice::arg_to_uint;
+
+    if args.len() > 2 {
+        return Err(BadNumberOfArgs("set-display".to_string(), "at most".to_string(), 2))
+    }
+
+    if args.len() == 0 {
+        return Ok(Atom(Symbol(env.display.to_string())))
+    }
+
+    let name = match args[0] {
+        Atom(Symbol(ref x)) => x.clone(),
+        _ => return Err(BadArgType("Display modes are given by name".to_string()))
+    };
+
+    let digits = if args.len() == 2 {
+        try!(arg_to_uint(try!(args[1].desymbolize(env))))
+    } else {
+        DEFAULT_DISPLAY_DIGITS
+    };
+
+    env.display = match DisplayMode::from_name(name.as_slice(), digits) {
+        Some(mode) => mode,
+        None => return Err(BadArgType(format!("Unknown display mode `{}'", name)))
+    };
+    Ok(Atom(Void))
 }
 
 pub trait Evaluate {
@@ -103,10 +295,7 @@ impl Evaluate for ArgType {
     fn eval(&self, env: &mut Environment) -> CalcResult {
         match self {
             &Atom(_) => Ok(self.clone()),
-            &SExpr(ref s) =>  match s.expr_type {
-                BuiltIn(x) => operator::eval(x, &s.args, env),
-                Function(ref f) => function::eval(f, &s.args, env)
-            }
+            &SExpr(ref s) => function::eval(s, env)
         }
     }
 
@@ -128,10 +317,7 @@ impl Evaluate for ArgType {
 
 impl Evaluate for Expression {
     fn eval(&self, env: &mut Environment) -> CalcResult {
-        match self.expr_type {
-            BuiltIn(x) => operator::eval(x, &self.args, env),
-            Function(ref f) => function::eval(f, &self.args, env)
-        }
+        function::eval(self, env)
     }
 
     fn arg_to_literal(&self, env: &mut Environment) -> CalcResult<LiteralType> {
@@ -3,10 +3,15 @@
 extern crate types;
 
 use std::num;
-use self::types::operator::{Add, Sub, Mul, Div, Rem, Arith};
-use self::types::literal::Matrix;
-use super::super::{CalcResult, Environment, BadNumberOfArgs, BadArgType, Evaluate};
-use super::{BigRational, ArgType, Atom, Lit};
+use std::num::{Signed, Zero};
+use self::types::operator::{Add, Sub, Mul, Div, Rem, Arith,
+                            ExactOp, ToInexact, ToExact, IsExact, IsInexact};
+use self::types::literal::{Matrix, BigNum, Float, Complex, Polynomial, Boolean, Symbol,
+                           to_float};
+use super::super::{CalcResult, Environment, BadNumberOfArgs, BadArgType, DivByZero, Evaluate};
+use super::{BigRational, Ratio, ArgType, Atom, Lit};
+use super::super::matrice::arg_to_uint;
+use super::super::pretty::radix_integer;
 
 pub type Args<T = ArgType> = Vec<T>;
 pub type BigR = BigRational;
@@ -22,6 +27,27 @@ fn minlen_op_ident(op: &Arith) -> (uint, |Lit, &Lit| -> Lit,  Lit) {
     }
 }
 
+/// Complex numbers and polynomials only have exact parts, so they can't be
+/// combined with a float without losing its inexactness. A number can't be
+/// divided by a polynomial either.
+fn check_operands(oper: &Arith, a: &Lit, b: &Lit) -> CalcResult<()> {
+    match (a, b) {
+        (&Float(_), &Complex(..)) | (&Float(_), &Polynomial(_)) => {
+            Err(BadArgType(format!("The inexact {} can't be combined with {}", a, b)))
+        },
+        (&Complex(..), &Float(_)) | (&Polynomial(_), &Float(_)) => {
+            Err(BadArgType(format!("The inexact {} can't be combined with {}", b, a)))
+        },
+        (&BigNum(_), &Polynomial(_)) if *oper == Div => {
+            Err(BadArgType("A number can't be divided by a polynomial".to_string()))
+        },
+        (&Polynomial(_), &BigNum(ref y)) if (*oper == Div || *oper == Rem) && y.is_zero() => {
+            Err(DivByZero)
+        },
+        _ => Ok(())
+    }
+}
+
 pub fn arith(args: &Args, env: &mut Env, oper: Arith) -> CalcResult {
     let (min_len, op, ident) = minlen_op_ident(&oper);
 
@@ -39,16 +65,62 @@ pub fn arith(args: &Args, env: &mut Env, oper: Arith) -> CalcResult {
                         Some(inverted) => Ok(Atom(Matrix(inverted))),
                         None => Err(BadArgType("Inversion failed".to_string()))
                 },
-                x => Ok(Atom(op(ident, &x)))
+                x => { try!(check_operands(&oper, &ident, &x)); Ok(Atom(op(ident, &x))) }
             },                    
             _ => Ok(Atom(op(ident, &try!(args[0].desymbolize(env)))))
         }
     } else {
         let mut answer = try!(args[0].desymbolize(env));
         for x in args.tail().iter() {
-            answer = op(answer, &try!(x.desymbolize(env)));
+            let x = try!(x.desymbolize(env));
+            try!(check_operands(&oper, &answer, &x));
+            answer = op(answer, &x);
         }
         Ok(Atom(answer))
     }
 }
 
+/// Conversions between exact and inexact numbers, and tests for exactness.
+pub fn exactness(args: &Args, env: &mut Env, op: ExactOp) -> CalcResult {
+    if args.len() != 1 {
+        return Err(BadNumberOfArgs(op.to_string(), "only".to_string(), 1))
+    }
+
+    let answer = match (op, try!(args[0].desymbolize(env))) {
+        (ToInexact, BigNum(x)) => Float(to_float(&x)),
+        (ToInexact, Float(x)) => Float(x),
+        (ToExact, Float(x)) => match Ratio::from_float(x) {
+            Some(exact) => BigNum(exact),
+            None => return Err(BadArgType(format!("{} has no exact value", x)))
+        },
+        (ToExact, BigNum(x)) => BigNum(x),
+        (ToExact, Complex(re, im)) => Complex(re, im),
+        (IsExact, BigNum(_)) | (IsExact, Complex(_, _)) => Boolean(true),
+        (IsInexact, Float(_)) => Boolean(true),
+        (IsExact, Float(_)) | (IsInexact, BigNum(_)) | (IsInexact, Complex(_, _)) => Boolean(false),
+        (_, x) => return Err(BadArgType(format!("`{}' is not defined for {}", op, x)))
+    };
+
+    Ok(Atom(answer))
+}
+
+/// Writes an integer in another base, e.g. (to-base 255 16) gives 0xff.
+pub fn to_base(args: &Args, env: &mut Env) -> CalcResult {
+    if args.len() != 2 {
+        return Err(BadNumberOfArgs("to-base".to_string(), "only".to_string(), 2))
+    }
+
+    let radix = try!(arg_to_uint(try!(args[1].desymbolize(env))));
+    if radix < 2 || radix > 36 {
+        return Err(BadArgType("Bases run from 2 to 36".to_string()))
+    }
+
+    match try!(args[0].desymbolize(env)) {
+        BigNum(ref x) if x.is_integer() => {
+            let sign = if x.is_negative() { "-" } else { "" };
+            let digits = radix_integer(&x.to_integer().abs(), radix);
+            Ok(Atom(Symbol(sign.to_string().append(digits.as_slice()))))
+        },
+        _ => Err(BadArgType("Only integers can be written in another base".to_string()))
+    }
+}
@@ -0,0 +1,422 @@
+//! Calculus on procedures of one variable. The procedures are evaluated at
+//! exact rational points, and the answers are correct to the working
+//! precision or to a tolerance given by the user.
+
+extern crate types;
+
+use std::num;
+use std::num::{Zero, Signed};
+use self::types::operator::{CalculusOp, Integrate, IntegrateWithError, Derivative, Limit};
+use self::types::literal::{BigNum, Float, List, Symbol};
+use super::super::{CalcResult, Environment, Evaluate, LiteralType, Expression, BadArgType,
+                   BadNumberOfArgs, BadFloatRange, NoConvergence};
+use super::{ArgType, Atom, BigRational, Ratio};
+use super::fixed;
+use super::fixed::{Fix, GUARD_BITS, big, one, bit_len, bits_for};
+use super::listops::proc_getter;
+use super::matrice::arg_to_uint;
+
+/// The number of times the step of the quadrature is halved before giving up.
+static MAX_LEVEL: uint = 10;
+
+/// Estimates from the first couple of steps agree by accident too easily.
+static MIN_LEVEL: uint = 2;
+
+/// The number of times the step is halved in Richardson extrapolation.
+static MAX_ROWS: uint = 12;
+static MIN_ROWS: uint = 2;
+
+/// Extra digits that functions are evaluated to when differences are taken.
+pub static GUARD_DIGITS: uint = 10;
+
+/// A procedure of one variable, with a frame of its own to bind the variable
+/// in, so that it can be called over and over without copying the environment.
+pub struct Function {
+    var: String,
+    body: Expression,
+    frame: Environment,
+    name: String,
+}
+
+impl Function {
+    pub fn new(args: &Vec<ArgType>, env: &mut Environment, name: &str) -> CalcResult<Function> {
+        let (names, body, scope) = try!(proc_getter(args, env));
+        if names.len() != 1 {
+            return Err(BadArgType(format!("`{}' needs a function of one variable", name)))
+        }
+
+        Ok(Function { var: names[0].clone(), body: body, frame: env.new_call_frame(&scope),
+                      name: name.to_string() })
+    }
+
+    /// Evaluates the function to more digits than the working precision.
+    pub fn extra_precision(&mut self, digits: uint) {
+        self.frame.precision = self.frame.precision + digits;
+    }
+
+    /// The value of the function at x, whatever its type.
+    pub fn apply(&mut self, x: LiteralType) -> CalcResult<LiteralType> {
+        self.frame.bind(self.var.clone(), x);
+        self.body.desymbolize(&mut self.frame)
+    }
+
+    pub fn at(&mut self, x: &BigRational) -> CalcResult<BigRational> {
+        let y = try!(self.apply(BigNum(x.clone())));
+        to_exact(y, self.name.as_slice())
+    }
+}
+
+pub fn to_exact(lit: LiteralType, name: &str) -> CalcResult<BigRational> {
+    match lit {
+        BigNum(x) => Ok(x),
+        Float(x) => match Ratio::from_float(x) {
+            Some(y) => Ok(y),
+            None => Err(BadArgType(format!("`{}' can't work with {}", name, x)))
+        },
+        x => Err(BadArgType(format!("`{}' expected a number, but was given {}", name, x)))
+    }
+}
+
+/// One end of an interval. The infinite ends are written #iinf and #i-inf.
+#[deriving(Clone, PartialEq)]
+pub enum Bound {
+    Finite(BigRational),
+    PosInf,
+    NegInf,
+}
+
+pub fn get_bound(arg: &ArgType, env: &mut Environment, name: &str) -> CalcResult<Bound> {
+    match try!(arg.desymbolize(env)) {
+        Float(x) if x.is_infinite() => Ok(if x > 0.0 { PosInf } else { NegInf }),
+        x => Ok(Finite(try!(to_exact(x, name))))
+    }
+}
+
+/// A positive tolerance, which defaults to one unit in the last place of the
+/// working precision.
+pub fn get_tolerance(args: &[ArgType], env: &mut Environment,
+                     name: &str) -> CalcResult<BigRational> {
+    if args.len() == 0 {
+        return Ok(Ratio::new(big(1), num::pow(big(10), env.precision)))
+    }
+
+    let tol = try!(to_exact(try!(args[0].desymbolize(env)), name));
+    if !tol.is_positive() {
+        return Err(BadArgType(format!("`{}' needs a positive tolerance", name)))
+    }
+    Ok(tol)
+}
+
+/// One node of the tanh-sinh rule on [-1, 1] at t > 0, as its distance from
+/// the nearest end and its weight. With v = e^(pi sinh t), the distance is
+/// s = 1 - tanh(pi/2 sinh t) = 2 / (v + 1), and the weight is
+/// pi/2 cosh t sech^2(pi/2 sinh t) = pi/2 cosh t s (2 - s). Keeping s exact,
+/// rather than the node itself, gives good points right up against the ends.
+/// Returns None once the nodes are closer to the ends than 2^-2q, which is far
+/// enough out to catch most of an integrable singularity at an end.
+fn node(t: &BigRational, pi: &Fix, q: uint) -> CalcResult<Option<(BigRational, BigRational)>> {
+    let e = try!(fixed::exp(t, q));
+    let e_inv = fixed::div(&one(q), &e, q);
+    let (sinh, cosh) = ((e - e_inv) >> 1, (e + e_inv) >> 1);
+
+    let v = try!(fixed::exp(&fixed::exact(&fixed::mul(pi, &sinh, q), q), q));
+
+    // v is large, so s is worked out to as many more bits as v has
+    let extra = bit_len(&v) - q;
+    let r = q + extra;
+    let s = (big(1) << (r + q + 1)) / (v + one(q));
+    if extra > q && bit_len(&s) <= extra - q {
+        return Ok(None)
+    }
+
+    let s = fixed::exact(&s, r);
+    let two: BigRational = Ratio::from_integer(big(2));
+    let weight = fixed::exact(&(fixed::mul(pi, &cosh, q) >> 1), q) * s * (two - s);
+    Ok(Some((s, weight)))
+}
+
+/// The point on [lo, hi] for a node at distance s from the upper or the lower
+/// end of [-1, 1], along with dx/dt there. Half infinite intervals are mapped
+/// to [0, 1) by x = lo + u / (1 - u), and the whole line by x = t / (1 - t^2).
+fn point(lo: &Bound, hi: &Bound, s: &BigRational, upper: bool) -> (BigRational, BigRational) {
+    let one_r: BigRational = num::one();
+    let two: BigRational = Ratio::from_integer(big(2));
+
+    match (lo, hi) {
+        (&Finite(ref a), &Finite(ref b)) => {
+            let d = (*b - *a) / two;
+            (if upper { *b - d * *s } else { *a + d * *s }, d)
+        },
+        (&NegInf, &PosInf) => {
+            let t = if upper { one_r - *s } else { *s - one_r };
+            let den = *s * (two - *s);
+            (t / den, (one_r + t * t) / (den * den))
+        },
+        _ => {
+            // 1 - u, where u = (1 + t) / 2
+            let rest = if upper { *s / two } else { one_r - *s / two };
+            let jac = one_r / (two * rest * rest);
+            let x = (one_r - rest) / rest;
+            match (lo, hi) {
+                (&Finite(ref a), _) => (*a + x, jac),
+                (_, &Finite(ref b)) => (*b - x, jac),
+                _ => fail!("Impossible!")
+            }
+        }
+    }
+}
+
+/// f(x) w dx/dt at one node.
+fn term(f: &mut Function, lo: &Bound, hi: &Bound, s: &BigRational, weight: &BigRational,
+        upper: bool, q: uint) -> CalcResult<Fix> {
+    let (x, jac) = point(lo, hi, s, upper);
+    let y = try!(f.at(&x));
+    Ok(fixed::from_rational(&(y * jac * *weight), q))
+}
+
+/// The sum of f(x) w dx/dt over the nodes that are new at a level, where the
+/// step is 2^-level. Each tail is cut off at the last node that can be told
+/// apart from the end, or once f grows too large to evaluate out there.
+fn level_sum(f: &mut Function, lo: &Bound, hi: &Bound, level: uint, pi: &Fix,
+             q: uint) -> CalcResult<Fix> {
+    let mut sum: Fix = Zero::zero();
+    let step = if level == 0 {
+        let one_r: BigRational = num::one();
+        let half_pi = fixed::exact(&(*pi >> 1), q);
+        sum = sum + try!(term(f, lo, hi, &one_r, &half_pi, true, q));
+        1
+    } else {
+        2
+    };
+
+    let (mut upper_done, mut lower_done) = (false, false);
+    let mut k = 1u;
+    while !(upper_done && lower_done) {
+        let t = Ratio::new(big(k), big(1) << level);
+        let (s, weight) = match try!(node(&t, pi, q)) {
+            Some(x) => x,
+            None => break
+        };
+
+        for &upper in [true, false].iter() {
+            let done = if upper { &mut upper_done } else { &mut lower_done };
+            if *done {
+                continue
+            }
+            match term(f, lo, hi, &s, &weight, upper, q) {
+                Ok(x) => sum = sum + x,
+                Err(BadFloatRange) => *done = true,
+                Err(e) => return Err(e)
+            }
+        }
+        k += step;
+    }
+
+    Ok(sum)
+}
+
+/// Tanh-sinh quadrature on lo < hi, halving the step until two estimates in a
+/// row agree to the tolerance. Returns the integral and the difference of the
+/// last two estimates, which is a generous bound on its error since the rule
+/// roughly doubles the number of correct digits at each level.
+fn tanh_sinh(f: &mut Function, lo: &Bound, hi: &Bound, tol: &BigRational,
+             q: uint) -> CalcResult<(BigRational, BigRational)> {
+    let pi = fixed::pi(q);
+    let mut sum = try!(level_sum(f, lo, hi, 0, &pi, q));
+    let mut last = fixed::exact(&sum, q);
+
+    for level in range(1, MAX_LEVEL + 1) {
+        sum = sum + try!(level_sum(f, lo, hi, level, &pi, q));
+        let estimate = fixed::exact(&sum, q + level);
+        let error = (estimate - last).abs();
+        if level >= MIN_LEVEL && error <= *tol {
+            return Ok((estimate, error))
+        }
+        last = estimate;
+    }
+
+    Err(NoConvergence(f.name.clone(), MAX_LEVEL))
+}
+
+/// The integral of f from a to b, and an estimate of its error.
+pub fn integrate(f: &mut Function, a: Bound, b: Bound, tol: &BigRational,
+                 env: &Environment) -> CalcResult<(BigRational, BigRational)> {
+    let flip = match (&a, &b) {
+        (&Finite(ref x), &Finite(ref y)) if x == y => return Ok((Zero::zero(), Zero::zero())),
+        (&PosInf, &PosInf) | (&NegInf, &NegInf) => return Ok((Zero::zero(), Zero::zero())),
+        (&Finite(ref x), &Finite(ref y)) => x > y,
+        (&PosInf, _) | (_, &NegInf) => true,
+        _ => false
+    };
+
+    // enough bits for the working precision and for the tolerance
+    let tol_bits = bit_len(&(tol.recip().ceil().to_integer())) + GUARD_BITS;
+    let q = num::max(bits_for(env.precision), tol_bits);
+
+    let (value, error) = if flip {
+        try!(tanh_sinh(f, &b, &a, tol, q))
+    } else {
+        try!(tanh_sinh(f, &a, &b, tol, q))
+    };
+
+    Ok((if flip { -value } else { value }, error))
+}
+
+/// Richardson extrapolation of a(h) as h is halved, for an a(h) whose error is
+/// a series in h^power. a is given the number of halvings, and the answer is
+/// taken once the last two extrapolations agree to the tolerance.
+pub fn richardson(a: |uint| -> CalcResult<BigRational>, power: uint, tol: &BigRational,
+              name: &str) -> CalcResult<BigRational> {
+    let mut prev = vec![try!(a(0))];
+
+    for i in range(1, MAX_ROWS + 1) {
+        let mut row = vec![try!(a(i))];
+        for j in range(1, i + 1) {
+            let factor = Ratio::from_integer((big(1) << (power * j)) - big(1));
+            let next = row[j - 1] + (row[j - 1] - prev[j - 1]) / factor;
+            row.push(next);
+        }
+
+        if i >= MIN_ROWS && (row[i] - prev[i - 1]).abs() <= *tol {
+            return Ok(row[i].clone())
+        }
+        prev = row;
+    }
+
+    Err(NoConvergence(name.to_string(), MAX_ROWS))
+}
+
+/// The nth derivative of f at x, from central differences
+/// sum (-1)^k C(n, k) f(x + (n/2 - k) h) / h^n, whose error is a series in h^2.
+pub fn derivative(f: &mut Function, x: &BigRational, n: uint,
+                  tol: &BigRational) -> CalcResult<BigRational> {
+    let one_r: BigRational = num::one();
+    let two: BigRational = Ratio::from_integer(big(2));
+    let order = Ratio::from_integer(big(n));
+
+    // small enough that the points stay on the same side of zero as x
+    let scale = if x.is_zero() || x.abs() > one_r { one_r.clone() } else { x.abs() };
+    let h0 = scale / (order * Ratio::from_integer(big(4)));
+    let name = f.name.clone();
+
+    richardson(|i| {
+        let h = h0 / Ratio::from_integer(big(1) << i);
+        let mut sum: BigRational = Zero::zero();
+        let mut coeff = one_r.clone();
+
+        for k in range(0, n + 1) {
+            let offset = (order / two - Ratio::from_integer(big(k))) * h;
+            let term = coeff * try!(f.at(&(*x + offset)));
+            sum = if k % 2 == 0 { sum + term } else { sum - term };
+            coeff = coeff * Ratio::from_integer(big(n - k)) / Ratio::from_integer(big(k + 1));
+        }
+
+        Ok(sum / num::pow(h, n))
+    }, 2, tol, name.as_slice())
+}
+
+/// The limit of f as x approaches a point from one side, or as x grows
+/// without bound. Near a point f(x0 + h) is taken to be a series in h, and far
+/// out f(1/h) is.
+pub fn one_sided_limit(f: &mut Function, at: &Bound, right: bool,
+                       tol: &BigRational) -> CalcResult<BigRational> {
+    let h0: BigRational = Ratio::new(big(1), big(4));
+    let name = f.name.clone();
+
+    richardson(|i| {
+        let h = h0 / Ratio::from_integer(big(1) << i);
+        let x = match *at {
+            Finite(ref x0) => if right { *x0 + h } else { *x0 - h },
+            PosInf => h.recip(),
+            NegInf => -h.recip(),
+        };
+        f.at(&x)
+    }, 1, tol, name.as_slice())
+}
+
+/// Reads the direction of a limit, given as 'left or 'right.
+fn get_direction(arg: &ArgType, name: &str) -> CalcResult<bool> {
+    match *arg {
+        Atom(Symbol(ref x)) if x.as_slice() == "left" => Ok(false),
+        Atom(Symbol(ref x)) if x.as_slice() == "right" => Ok(true),
+        _ => Err(BadArgType(format!("`{}' approaches from 'left or 'right", name)))
+    }
+}
+
+pub fn calculus(args: &Vec<ArgType>, env: &mut Environment, op: CalculusOp) -> CalcResult {
+    let name = op.to_string();
+    let name = name.as_slice();
+
+    let (min, max) = match op {
+        Integrate | IntegrateWithError => (3, 4),
+        Derivative | Limit => (2, 3),
+    };
+
+    if args.len() < min || args.len() > max {
+        return Err(if args.len() < min {
+            BadNumberOfArgs(name.to_string(), "at least".to_string(), min)
+        } else {
+            BadNumberOfArgs(name.to_string(), "at most".to_string(), max)
+        })
+    }
+
+    let mut f = try!(Function::new(args, env, name));
+    let tol = Ratio::new(big(1), num::pow(big(10), env.precision));
+
+    let answer = match op {
+        Integrate | IntegrateWithError => {
+            let a = try!(get_bound(&args[1], env, name));
+            let b = try!(get_bound(&args[2], env, name));
+            let tol = try!(get_tolerance(args.slice_from(3), env, name));
+
+            let (value, error) = try!(integrate(&mut f, a, b, &tol, env));
+            let value = BigNum(fixed::round_rational(&value, env.precision));
+
+            if op == Integrate {
+                value
+            } else {
+                List(vec![value, BigNum(fixed::round_rational(&error, env.precision))])
+            }
+        },
+        Derivative => {
+            let x = try!(to_exact(try!(args[1].desymbolize(env)), name));
+            let n = if args.len() == 3 {
+                try!(arg_to_uint(try!(args[2].desymbolize(env))))
+            } else {
+                1
+            };
+            if n == 0 {
+                return Err(BadArgType(format!("`{}' needs an order of at least 1", name)))
+            }
+
+            // differences lose digits, so f is evaluated to more of them
+            f.extra_precision(4 * n + GUARD_DIGITS);
+            BigNum(fixed::round_rational(&try!(derivative(&mut f, &x, n, &tol)), env.precision))
+        },
+        Limit => {
+            let at = try!(get_bound(&args[1], env, name));
+            f.extra_precision(GUARD_DIGITS);
+
+            let limit = match at {
+                Finite(_) if args.len() == 3 => {
+                    let right = try!(get_direction(&args[2], name));
+                    try!(one_sided_limit(&mut f, &at, right, &tol))
+                },
+                Finite(ref x0) => {
+                    let left = try!(one_sided_limit(&mut f, &at, false, &tol));
+                    let right = try!(one_sided_limit(&mut f, &at, true, &tol));
+                    if (left - right).abs() > tol {
+                        return Err(BadArgType(format!("The limits from either side of {} differ",
+                                                      x0)))
+                    }
+                    (left + right) / Ratio::from_integer(big(2))
+                },
+                // there is only one way to go to infinity
+                _ => try!(one_sided_limit(&mut f, &at, false, &tol))
+            };
+            BigNum(fixed::round_rational(&limit, env.precision))
+        },
+    };
+
+    Ok(Atom(answer))
+}
@@ -0,0 +1,221 @@
+//! Combinatorics, with exact big integer results.
+
+extern crate types;
+
+use std::num;
+use std::num::Signed;
+use self::types::operator::{Combinatoric, Factorial, Choose, Permutations, Multinomial,
+                            Stirling1, Stirling2, Bell, Catalan, Partitions, Fib};
+use super::super::{CalcResult, Environment, Evaluate, BadArgType, BadNumberOfArgs};
+use super::{ArgType, Atom};
+use super::bigint::BigInt;
+use super::fixed::big;
+use super::numtheory::{int_lit, lit_to_int};
+
+/// The product of the integers from lo to hi inclusive, multiplying balanced
+/// halves so the operands stay about the same size.
+pub fn product(lo: uint, hi: uint) -> BigInt {
+    if lo > hi {
+        return num::one()
+    }
+
+    if hi - lo < 8 {
+        let mut acc = big(lo);
+        for i in range(lo + 1, hi + 1) {
+            acc = acc * big(i);
+        }
+        return acc
+    }
+
+    let mid = lo + (hi - lo) / 2;
+    product(lo, mid) * product(mid + 1, hi)
+}
+
+pub fn factorial(n: uint) -> BigInt {
+    product(1, n)
+}
+
+pub fn choose(n: uint, k: uint) -> BigInt {
+    if k > n {
+        return num::zero()
+    }
+
+    let k = if k > n - k { n - k } else { k };
+    product(n - k + 1, n) / factorial(k)
+}
+
+/// Unsigned Stirling numbers of the first kind, the number of permutations of n
+/// elements with k cycles.
+pub fn stirling1(n: uint, k: uint) -> BigInt {
+    let mut row: Vec<BigInt> = vec![num::one()];
+
+    for i in range(0, n) {
+        let mut next: Vec<BigInt> = Vec::from_elem(i + 2, num::zero());
+        for j in range(0, i + 1) {
+            let (up, across) = (next[j + 1] + row[j], next[j] + row[j] * big(i));
+            *next.get_mut(j + 1) = up;
+            *next.get_mut(j) = across;
+        }
+        row = next;
+    }
+
+    if k <= n { row[k].clone() } else { num::zero() }
+}
+
+/// Stirling numbers of the second kind, the number of ways to partition n
+/// elements into k non-empty sets.
+pub fn stirling2(n: uint, k: uint) -> BigInt {
+    let mut row: Vec<BigInt> = vec![num::one()];
+
+    for i in range(0, n) {
+        let mut next: Vec<BigInt> = Vec::from_elem(i + 2, num::zero());
+        for j in range(0, i + 1) {
+            let (up, across) = (next[j + 1] + row[j], next[j] + row[j] * big(j));
+            *next.get_mut(j + 1) = up;
+            *next.get_mut(j) = across;
+        }
+        row = next;
+    }
+
+    if k <= n { row[k].clone() } else { num::zero() }
+}
+
+/// Bell numbers, by the Bell triangle.
+pub fn bell(n: uint) -> BigInt {
+    let mut row: Vec<BigInt> = vec![num::one()];
+
+    for _ in range(0, n) {
+        let mut next = vec![row.last().unwrap().clone()];
+        for x in row.iter() {
+            let sum = *next.last().unwrap() + *x;
+            next.push(sum);
+        }
+        row = next;
+    }
+
+    row[0].clone()
+}
+
+pub fn catalan(n: uint) -> BigInt {
+    choose(2 * n, n) / big(n + 1)
+}
+
+/// The number of partitions of n, by Euler's pentagonal number theorem.
+pub fn partitions(n: uint) -> BigInt {
+    let mut p: Vec<BigInt> = vec![num::one()];
+
+    for m in range(1, n + 1) {
+        let mut total: BigInt = num::zero();
+        let mut k = 1u;
+        loop {
+            let first = k * (3 * k - 1) / 2;
+            if first > m {
+                break
+            }
+            let second = k * (3 * k + 1) / 2;
+            let mut term = p[m - first].clone();
+            if second <= m {
+                term = term + p[m - second];
+            }
+            total = if k % 2 == 1 { total + term } else { total - term };
+            k += 1;
+        }
+        p.push(total);
+    }
+
+    p[n].clone()
+}
+
+/// Fibonacci numbers by fast doubling, returning (F(n), F(n + 1)).
+fn fib_pair(n: uint) -> (BigInt, BigInt) {
+    if n == 0 {
+        return (num::zero(), num::one())
+    }
+
+    let (a, b) = fib_pair(n / 2);
+    let c = a * ((b << 1) - a);
+    let d = a * a + b * b;
+    if n % 2 == 0 { (c, d) } else { (d.clone(), c + d) }
+}
+
+/// Fibonacci numbers, extended to negative n by F(-n) = (-1)^(n + 1) F(n).
+pub fn fib(n: &BigInt) -> CalcResult<BigInt> {
+    let m = match n.abs().to_uint() {
+        Some(m) => m,
+        None => return Err(BadArgType(format!("{} is too large for `fib'", n)))
+    };
+
+    let (f, _) = fib_pair(m);
+    Ok(if n.is_negative() && m % 2 == 0 { -f } else { f })
+}
+
+fn to_count(x: &BigInt, name: &str) -> CalcResult<uint> {
+    if x.is_negative() {
+        return Err(BadArgType(format!("`{}' is only defined for non-negative integers", name)))
+    }
+
+    match x.to_uint() {
+        Some(n) => Ok(n),
+        None => Err(BadArgType(format!("{} is too large for `{}'", x, name)))
+    }
+}
+
+pub fn combinatorics(args: &Vec<ArgType>, env: &mut Environment, op: Combinatoric) -> CalcResult {
+    let name = op.to_string();
+    let name = name.as_slice();
+
+    let needed = match op {
+        Choose | Permutations | Stirling1 | Stirling2 => 2,
+        Multinomial => args.len(),
+        _ => 1,
+    };
+
+    if args.len() != needed || args.len() == 0 {
+        return Err(if op == Multinomial {
+            BadNumberOfArgs(name.to_string(), "at least".to_string(), 1)
+        } else {
+            BadNumberOfArgs(name.to_string(), "only".to_string(), needed)
+        })
+    }
+
+    let mut ints = Vec::new();
+    for arg in args.iter() {
+        ints.push(try!(lit_to_int(&try!(arg.desymbolize(env)), name)));
+    }
+
+    if op == Fib {
+        return Ok(Atom(int_lit(try!(fib(&ints[0])))))
+    }
+
+    let mut counts = Vec::new();
+    for x in ints.iter() {
+        counts.push(try!(to_count(x, name)));
+    }
+
+    let answer = match op {
+        Factorial => factorial(counts[0]),
+        Choose => choose(counts[0], counts[1]),
+        Permutations => if counts[1] > counts[0] {
+            num::zero()
+        } else {
+            product(counts[0] - counts[1] + 1, counts[0])
+        },
+        Multinomial => {
+            let mut total = 0u;
+            let mut acc: BigInt = num::one();
+            for &k in counts.iter() {
+                total += k;
+                acc = acc * choose(total, k);
+            }
+            acc
+        },
+        Stirling1 => stirling1(counts[0], counts[1]),
+        Stirling2 => stirling2(counts[0], counts[1]),
+        Bell => bell(counts[0]),
+        Catalan => catalan(counts[0]),
+        Partitions => partitions(counts[0]),
+        Fib => fail!("Impossible!"),
+    };
+
+    Ok(Atom(int_lit(answer)))
+}
@@ -0,0 +1,347 @@
+//! Complex numbers with exact rational parts, and the transcendental
+//! functions extended to them.
+
+extern crate types;
+
+use std::num;
+use std::num::{Zero, Signed};
+use self::types::operator::{ComplexOp, RealPart, ImagPart, Conj, Abs, Arg,
+                            Sin, Cos, Tan, ASin, ACos, ATan, SinH, CosH, TanH,
+                            ASinH, ACosH, ATanH, Log, Ln, Exp, Transcendental};
+use self::types::literal::{BigNum, Complex, LiteralType, complex};
+use super::super::{CalcResult, Environment, Evaluate, BadArgType, BadNumberOfArgs,
+                   BadPowerRange, DivByZero};
+use super::super::constants::constant;
+use super::{BigRational, Ratio, ArgType, Atom};
+use super::{fixed, trig, power};
+
+pub type Cx = (BigRational, BigRational);
+
+/// Decimal digits carried beyond the requested precision while functions are
+/// composed, so that the final rounding is still correct.
+static GUARD_DIGITS: uint = 10;
+
+pub fn to_complex(lit: &LiteralType) -> Option<Cx> {
+    match lit {
+        &BigNum(ref x) => Some((x.clone(), num::zero())),
+        &Complex(ref re, ref im) => Some((re.clone(), im.clone())),
+        _ => None
+    }
+}
+
+pub fn to_lit(z: Cx) -> LiteralType {
+    let (re, im) = z;
+    complex(re, im)
+}
+
+fn real(x: BigRational) -> Cx {
+    (x, num::zero())
+}
+
+pub fn add(a: &Cx, b: &Cx) -> Cx {
+    let (ref p, ref q) = *a;
+    let (ref r, ref s) = *b;
+    (*p + *r, *q + *s)
+}
+
+pub fn sub(a: &Cx, b: &Cx) -> Cx {
+    let (ref p, ref q) = *a;
+    let (ref r, ref s) = *b;
+    (*p - *r, *q - *s)
+}
+
+pub fn mul(a: &Cx, b: &Cx) -> Cx {
+    let (ref p, ref q) = *a;
+    let (ref r, ref s) = *b;
+    (*p * *r - *q * *s, *p * *s + *q * *r)
+}
+
+pub fn div(a: &Cx, b: &Cx) -> CalcResult<Cx> {
+    let (ref p, ref q) = *a;
+    let (ref r, ref s) = *b;
+    let denom = *r * *r + *s * *s;
+    if denom.is_zero() {
+        return Err(DivByZero)
+    }
+    Ok(((*p * *r + *q * *s) / denom, (*q * *r - *p * *s) / denom))
+}
+
+/// Multiplies by i.
+fn times_i(a: &Cx) -> Cx {
+    let (ref re, ref im) = *a;
+    (-im, re.clone())
+}
+
+fn halve(a: &Cx) -> Cx {
+    let (ref re, ref im) = *a;
+    let two: BigRational = Ratio::from_integer(fixed::big(2));
+    (*re / two, *im / two)
+}
+
+fn round(a: &Cx, digits: uint) -> Cx {
+    let (ref re, ref im) = *a;
+    (fixed::round_rational(re, digits), fixed::round_rational(im, digits))
+}
+
+fn pi(digits: uint) -> BigRational {
+    constant("pi", digits).unwrap()
+}
+
+/// |z|, exact when it is rational.
+pub fn modulus(z: &Cx, digits: uint) -> CalcResult<BigRational> {
+    let (ref re, ref im) = *z;
+    power::nth_root(&(*re * *re + *im * *im), 2, digits)
+}
+
+/// The principal argument of z, in (-pi, pi].
+pub fn arg(z: &Cx, digits: uint) -> CalcResult<BigRational> {
+    let (ref re, ref im) = *z;
+    let wd = digits + GUARD_DIGITS;
+
+    if re.is_zero() {
+        if im.is_zero() {
+            return Err(BadArgType("The argument of zero is undefined".to_string()))
+        }
+        let half_pi = pi(digits) / Ratio::from_integer(fixed::big(2));
+        return Ok(if im.is_negative() { -half_pi } else { half_pi })
+    }
+
+    let angle = try!(trig::transcend(&(*im / *re), ATan, wd));
+    let angle = if re.is_positive() {
+        angle
+    } else if im.is_negative() {
+        angle - pi(wd)
+    } else {
+        angle + pi(wd)
+    };
+
+    Ok(fixed::round_rational(&angle, digits))
+}
+
+/// The principal square root, exact when both parts are rational.
+pub fn sqrt(z: &Cx, digits: uint) -> CalcResult<Cx> {
+    let (ref re, ref im) = *z;
+    if im.is_zero() && !re.is_negative() {
+        return Ok(real(try!(power::nth_root(re, 2, digits))))
+    }
+
+    let two: BigRational = Ratio::from_integer(fixed::big(2));
+    let m = try!(modulus(z, digits));
+    let a = try!(power::nth_root(&((m + *re) / two), 2, digits));
+    let b = try!(power::nth_root(&((m - *re) / two), 2, digits));
+
+    Ok((a, if im.is_negative() { -b } else { b }))
+}
+
+pub fn exp(z: &Cx, wd: uint) -> CalcResult<Cx> {
+    let (ref re, ref im) = *z;
+    let scale = try!(trig::transcend(re, Exp, wd));
+    let cos = try!(trig::transcend(im, Cos, wd));
+    let sin = try!(trig::transcend(im, Sin, wd));
+    Ok((scale * cos, scale * sin))
+}
+
+/// The principal logarithm, ln |z| + i arg(z).
+pub fn ln(z: &Cx, wd: uint) -> CalcResult<Cx> {
+    let (ref re, ref im) = *z;
+    let sq = *re * *re + *im * *im;
+    if sq.is_zero() {
+        return Err(BadArgType("The logarithm of zero is undefined".to_string()))
+    }
+
+    let two: BigRational = Ratio::from_integer(fixed::big(2));
+    let ln_mod = try!(trig::transcend(&sq, Ln, wd)) / two;
+    Ok((ln_mod, try!(arg(z, wd))))
+}
+
+fn sin_cos(z: &Cx, wd: uint) -> CalcResult<(Cx, Cx)> {
+    let (ref re, ref im) = *z;
+    let (sin, cos) = (try!(trig::transcend(re, Sin, wd)), try!(trig::transcend(re, Cos, wd)));
+    let (sinh, cosh) = (try!(trig::transcend(im, SinH, wd)),
+                        try!(trig::transcend(im, CosH, wd)));
+
+    Ok(((sin * cosh, cos * sinh), (cos * cosh, -(sin * sinh))))
+}
+
+fn sinh_cosh(z: &Cx, wd: uint) -> CalcResult<(Cx, Cx)> {
+    let (ref re, ref im) = *z;
+    let (sin, cos) = (try!(trig::transcend(im, Sin, wd)), try!(trig::transcend(im, Cos, wd)));
+    let (sinh, cosh) = (try!(trig::transcend(re, SinH, wd)),
+                        try!(trig::transcend(re, CosH, wd)));
+
+    Ok(((sinh * cos, cosh * sin), (cosh * cos, sinh * sin)))
+}
+
+/// Evaluates a transcendental function of a complex number to `digits`
+/// decimal places, using the usual principal branches.
+pub fn transcend(z: &Cx, fop: Transcendental, digits: uint) -> CalcResult<Cx> {
+    let wd = digits + GUARD_DIGITS;
+    let one: Cx = real(num::one());
+
+    let answer = match fop {
+        Exp => try!(exp(z, wd)),
+        Ln => try!(ln(z, wd)),
+        Log => {
+            let ten = real(Ratio::from_integer(fixed::big(10)));
+            try!(div(&try!(ln(z, wd)), &try!(ln(&ten, wd))))
+        },
+        Sin => {
+            let (sin, _) = try!(sin_cos(z, wd));
+            sin
+        },
+        Cos => {
+            let (_, cos) = try!(sin_cos(z, wd));
+            cos
+        },
+        Tan => {
+            let (sin, cos) = try!(sin_cos(z, wd));
+            try!(div(&sin, &cos))
+        },
+        SinH => {
+            let (sinh, _) = try!(sinh_cosh(z, wd));
+            sinh
+        },
+        CosH => {
+            let (_, cosh) = try!(sinh_cosh(z, wd));
+            cosh
+        },
+        TanH => {
+            let (sinh, cosh) = try!(sinh_cosh(z, wd));
+            try!(div(&sinh, &cosh))
+        },
+        // asin z = -i ln(iz + sqrt(1 - z^2))
+        ASin | ACos => {
+            let root = try!(sqrt(&sub(&one, &mul(z, z)), wd));
+            let (re, im) = try!(ln(&add(&times_i(z), &root), wd));
+            let asin = (im, -re);
+            if fop == ASin {
+                asin
+            } else {
+                let half_pi = real(pi(wd) / Ratio::from_integer(fixed::big(2)));
+                sub(&half_pi, &asin)
+            }
+        },
+        // atan z = i/2 (ln(1 - iz) - ln(1 + iz))
+        ATan => {
+            let iz = times_i(z);
+            let diff = sub(&try!(ln(&sub(&one, &iz), wd)), &try!(ln(&add(&one, &iz), wd)));
+            halve(&times_i(&diff))
+        },
+        // asinh z = ln(z + sqrt(z^2 + 1))
+        ASinH => {
+            let root = try!(sqrt(&add(&mul(z, z), &one), wd));
+            try!(ln(&add(z, &root), wd))
+        },
+        // acosh z = ln(z + sqrt(z + 1) sqrt(z - 1))
+        ACosH => {
+            let root = mul(&try!(sqrt(&add(z, &one), wd)), &try!(sqrt(&sub(z, &one), wd)));
+            try!(ln(&add(z, &root), wd))
+        },
+        // atanh z = (ln(1 + z) - ln(1 - z)) / 2
+        ATanH => {
+            halve(&sub(&try!(ln(&add(&one, z), wd)), &try!(ln(&sub(&one, z), wd))))
+        },
+    };
+
+    Ok(round(&answer, digits))
+}
+
+/// Is a real argument outside the domain where `fop` has a real value?
+pub fn needs_complex(x: &BigRational, fop: Transcendental) -> bool {
+    let one: BigRational = num::one();
+    match fop {
+        ASin | ACos => x.abs() > one,
+        ACosH => *x < one,
+        ATanH => x.abs() > one,
+        Ln | Log => x.is_negative(),
+        _ => false
+    }
+}
+
+/// Raises a complex number to an integer power exactly.
+pub fn powi(z: &Cx, n: &BigRational) -> CalcResult<Cx> {
+    let mut power = match n.numer().abs().to_u64() {
+        Some(x) => x,
+        None => return Err(BadPowerRange)
+    };
+
+    let mut base = z.clone();
+    let mut result = real(num::one());
+    while power > 0 {
+        if power % 2 == 1 {
+            result = mul(&result, &base);
+        }
+        power /= 2;
+        base = mul(&base, &base);
+    }
+
+    if n.is_negative() {
+        div(&real(num::one()), &result)
+    } else {
+        Ok(result)
+    }
+}
+
+/// Raises z to the power w. Integer powers are exact, as are the roots of
+/// negative numbers whose moduli have rational roots and whose angle is a
+/// multiple of a right angle, e.g. (-4)^(1/2) = 2i.
+pub fn pow(z: &Cx, w: &Cx, digits: uint) -> CalcResult<Cx> {
+    let (ref w_re, ref w_im) = *w;
+
+    if w_im.is_zero() && w_re.is_integer() {
+        return powi(z, w_re)
+    }
+
+    let (ref z_re, ref z_im) = *z;
+    if z_re.is_zero() && z_im.is_zero() {
+        return if w_re.is_positive() {
+            Ok(real(num::zero()))
+        } else {
+            Err(DivByZero)
+        }
+    }
+
+    let wd = digits + GUARD_DIGITS;
+
+    if w_im.is_zero() && z_im.is_zero() && z_re.is_negative() {
+        let magnitude = real(try!(power::pow(&-z_re, w_re, wd)));
+        let rotation = if *w_re.denom() == fixed::big(2) {
+            match w_re.numer().to_int() {
+                Some(n) => match ((n % 4) + 4) % 4 {
+                    1 => (num::zero(), num::one()),
+                    _ => (num::zero(), -num::one::<BigRational>()),
+                },
+                None => return Err(BadPowerRange)
+            }
+        } else {
+            let angle = pi(wd) * *w_re;
+            (try!(trig::transcend(&angle, Cos, wd)), try!(trig::transcend(&angle, Sin, wd)))
+        };
+        return Ok(round(&mul(&magnitude, &rotation), digits))
+    }
+
+    let log = try!(ln(z, wd));
+    Ok(round(&try!(exp(&mul(w, &log), wd)), digits))
+}
+
+pub fn complex_ops(args: &Vec<ArgType>, env: &mut Environment, cop: ComplexOp) -> CalcResult {
+    if args.len() != 1 {
+        return Err(BadNumberOfArgs(cop.to_string(), "only".to_string(), 1))
+    }
+
+    let z = match to_complex(&try!(args[0].desymbolize(env))) {
+        Some(z) => z,
+        None => return Err(BadArgType(format!("`{}' is only defined for numbers", cop)))
+    };
+
+    let (re, im) = z.clone();
+    let answer = match cop {
+        RealPart => BigNum(re),
+        ImagPart => BigNum(im),
+        Conj => complex(re, -im),
+        Abs => BigNum(try!(modulus(&z, env.precision))),
+        Arg => BigNum(try!(arg(&z, env.precision))),
+    };
+
+    Ok(Atom(answer))
+}
@@ -0,0 +1,416 @@
+//! Probability distributions. The discrete ones are exact, apart from the
+//! factor of e^-lambda in the Poisson distribution, and the continuous ones
+//! are correct to the working precision. Each can also be sampled from.
+
+extern crate types;
+
+use std::num;
+use std::num::{Zero, Signed};
+use self::types::operator::{Distribution, DistFn, Normal, Binomial, Poisson, Uniform,
+                            Exponential, StudentT, ChiSquared, FisherF, Density, Cumulative,
+                            InverseCumulative, Draw};
+use self::types::literal::BigNum;
+use super::super::{CalcResult, Environment, Evaluate, BadArgType, BadNumberOfArgs, DivByZero};
+use super::{ArgType, Atom, BigRational, Ratio};
+use super::combinatorics::choose;
+use super::fixed;
+use super::fixed::{Fix, GUARD_BITS, big, one};
+use super::gamma::{gamma_p, beta_i, ln_gamma, ln_beta, ln_pi, exp_fixed, scale};
+use super::random::open_unit;
+
+/// The most trials of a binomial distribution, which are summed one at a time.
+static MAX_TRIALS: uint = 10000;
+
+/// The highest rate of a Poisson distribution, which e^-lambda needs about
+/// 1.5 lambda extra bits for.
+static MAX_RATE: uint = 10000;
+
+fn half(x: &BigRational) -> BigRational {
+    *x / Ratio::from_integer(big(2))
+}
+
+fn ln(x: &BigRational, p: uint) -> CalcResult<Fix> {
+    fixed::ln(x, p)
+}
+
+/// The value x where an increasing cdf reaches `target`, by bisection to p bits.
+/// The search starts on [lo, hi] and widens until the target is bracketed,
+/// only upwards if lo is the bottom of the support.
+fn invert(cdf: |&BigRational| -> CalcResult<Fix>, target: &BigRational, lo: BigRational,
+          hi: BigRational, bounded_below: bool, p: uint) -> CalcResult<BigRational> {
+    let two: BigRational = Ratio::from_integer(big(2));
+    let t = fixed::from_rational(target, p);
+    let (mut lo, mut hi) = (lo, hi);
+
+    while try!(cdf(&hi)) < t {
+        let width = hi - lo;
+        lo = hi.clone();
+        hi = hi + width * two;
+    }
+    while !bounded_below && try!(cdf(&lo)) > t {
+        let width = hi - lo;
+        hi = lo.clone();
+        lo = lo - width * two;
+    }
+
+    let eps = fixed::exact(&big(1), p);
+    while hi - lo > eps {
+        let mid = half(&(lo + hi));
+        if try!(cdf(&mid)) < t {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    Ok(half(&(lo + hi)))
+}
+
+fn normal_pdf(x: &BigRational, mu: &BigRational, sigma: &BigRational, p: uint) -> CalcResult<Fix> {
+    let q = p + GUARD_BITS;
+    let z = (*x - *mu) / *sigma;
+    let ln_2pi = fixed::ln2(q) + try!(ln_pi(q));
+    let density = try!(exp_fixed(&(-fixed::from_rational(&half(&(z * z)), q)
+                                   - try!(ln(sigma, q)) - (ln_2pi >> 1)), q));
+    Ok(fixed::rescale(&density, q, p))
+}
+
+/// Phi(z) = (1 + erf(z / sqrt 2)) / 2, where erf(y) = P(1/2, y^2).
+fn normal_cdf(x: &BigRational, mu: &BigRational, sigma: &BigRational, p: uint) -> CalcResult<Fix> {
+    let z = (*x - *mu) / *sigma;
+    let tail = try!(gamma_p(&half(&num::one()), &half(&(z * z)), p)) >> 1;
+    let middle = one(p) >> 1;
+    Ok(if z.is_negative() { middle - tail } else { middle + tail })
+}
+
+fn exponential_cdf(x: &BigRational, rate: &BigRational, p: uint) -> CalcResult<Fix> {
+    if x.is_negative() {
+        return Ok(Zero::zero())
+    }
+    Ok(one(p) - try!(fixed::exp(&-(*rate * *x), p)))
+}
+
+fn t_pdf(t: &BigRational, nu: &BigRational, p: uint) -> CalcResult<Fix> {
+    let q = p + GUARD_BITS;
+    let one_r: BigRational = num::one();
+    let ln_density = scale(&try!(ln(&(one_r + *t * *t / *nu), q)), &-half(&(*nu + one_r)))
+        - (try!(ln(nu, q)) >> 1) - try!(ln_beta(&half(&one_r), &half(nu), q));
+    Ok(fixed::rescale(&try!(exp_fixed(&ln_density, q)), q, p))
+}
+
+fn t_cdf(t: &BigRational, nu: &BigRational, p: uint) -> CalcResult<Fix> {
+    let one_r: BigRational = num::one();
+    let x = *nu / (*nu + *t * *t);
+    let tail = try!(beta_i(&half(nu), &half(&one_r), &x, p)) >> 1;
+    Ok(if t.is_negative() { tail } else { one(p) - tail })
+}
+
+/// A density like x^(a - 1) ... that is infinite, one or zero at x = 0, for
+/// a below, at or above one.
+fn density_at_zero(a: &BigRational, at_one: BigRational, p: uint) -> CalcResult<Fix> {
+    let one_r: BigRational = num::one();
+    if *a < one_r {
+        Err(DivByZero)
+    } else if *a == one_r {
+        Ok(fixed::from_rational(&at_one, p))
+    } else {
+        Ok(Zero::zero())
+    }
+}
+
+fn chi_squared_pdf(x: &BigRational, k: &BigRational, p: uint) -> CalcResult<Fix> {
+    let a = half(k);
+    if x.is_negative() {
+        return Ok(Zero::zero())
+    }
+    if x.is_zero() {
+        return density_at_zero(&a, half(&num::one()), p)
+    }
+
+    let q = p + GUARD_BITS;
+    let one_r: BigRational = num::one();
+    let ln_density = scale(&try!(ln(x, q)), &(a - one_r)) - fixed::from_rational(&half(x), q)
+        - scale(&fixed::ln2(q), &a) - try!(ln_gamma(&a, q));
+    Ok(fixed::rescale(&try!(exp_fixed(&ln_density, q)), q, p))
+}
+
+fn chi_squared_cdf(x: &BigRational, k: &BigRational, p: uint) -> CalcResult<Fix> {
+    gamma_p(&half(k), &half(x), p)
+}
+
+fn f_pdf(x: &BigRational, d1: &BigRational, d2: &BigRational, p: uint) -> CalcResult<Fix> {
+    if x.is_negative() {
+        return Ok(Zero::zero())
+    }
+    if x.is_zero() {
+        return density_at_zero(&half(d1), num::one(), p)
+    }
+
+    let q = p + GUARD_BITS;
+    let d1x = *d1 * *x;
+    let ln_density = scale(&try!(ln(&d1x, q)), &half(d1)) + scale(&try!(ln(d2, q)), &half(d2))
+        - scale(&try!(ln(&(d1x + *d2), q)), &half(&(*d1 + *d2)))
+        - try!(ln(x, q)) - try!(ln_beta(&half(d1), &half(d2), q));
+    Ok(fixed::rescale(&try!(exp_fixed(&ln_density, q)), q, p))
+}
+
+fn f_cdf(x: &BigRational, d1: &BigRational, d2: &BigRational, p: uint) -> CalcResult<Fix> {
+    if !x.is_positive() {
+        return Ok(Zero::zero())
+    }
+    let d1x = *d1 * *x;
+    beta_i(&half(d1), &half(d2), &(d1x / (d1x + *d2)), p)
+}
+
+pub fn binomial_pmf(k: &BigRational, n: uint, prob: &BigRational) -> BigRational {
+    let zero: BigRational = num::zero();
+    if !k.is_integer() {
+        return zero
+    }
+    let k = match k.to_integer().to_uint() {
+        Some(k) if k <= n => k,
+        _ => return zero
+    };
+
+    let one_r: BigRational = num::one();
+    Ratio::from_integer(choose(n, k)) * num::pow(prob.clone(), k)
+        * num::pow(one_r - *prob, n - k)
+}
+
+/// The exact cdf of the binomial distribution at each of 0, 1, ..., n.
+fn binomial_cdfs(n: uint, prob: &BigRational) -> Vec<BigRational> {
+    let mut total: BigRational = num::zero();
+    let mut cdfs = Vec::new();
+
+    for k in range(0, n + 1) {
+        total = total + binomial_pmf(&Ratio::from_integer(big(k)), n, prob);
+        cdfs.push(total.clone());
+    }
+    cdfs
+}
+
+/// The number of trials of a binomial distribution, if there aren't too many.
+fn trials(n: &BigRational, name: &str) -> CalcResult<uint> {
+    match n.to_integer().to_uint() {
+        Some(n) if n <= MAX_TRIALS => Ok(n),
+        _ => Err(BadArgType(format!("`{}' takes at most {} trials", name, MAX_TRIALS)))
+    }
+}
+
+/// e^-lambda, with enough bits that multiplying it by anything up to e^lambda
+/// still leaves p good bits. Returns the value and its precision.
+fn poisson_weight(lambda: &BigRational, p: uint) -> CalcResult<(Fix, uint)> {
+    let extra = match (*lambda * Ratio::from_integer(big(3)) / Ratio::from_integer(big(2)))
+                          .ceil().to_integer().to_uint() {
+        Some(x) => x,
+        None => return Err(BadArgType(format!("A Poisson rate can be at most {}", MAX_RATE)))
+    };
+    let q = p + extra + GUARD_BITS;
+    Ok((try!(fixed::exp(&-*lambda, q)), q))
+}
+
+/// The terms lambda^k / k! of the Poisson distribution, before the factor of
+/// e^-lambda, while `more` says to keep going.
+fn poisson_terms(lambda: &BigRational, more: |uint, &BigRational| -> bool) -> Vec<BigRational> {
+    let mut term: BigRational = num::one();
+    let mut terms = Vec::new();
+    let mut k = 0u;
+
+    while more(k, &term) {
+        terms.push(term.clone());
+        k += 1;
+        term = term * *lambda / Ratio::from_integer(big(k));
+    }
+    terms
+}
+
+fn exact_number(arg: &ArgType, env: &mut Environment, name: &str) -> CalcResult<BigRational> {
+    match try!(arg.desymbolize(env)) {
+        BigNum(x) => Ok(x),
+        x => Err(BadArgType(format!("`{}' only works on exact numbers, not {}", name, x)))
+    }
+}
+
+fn check(ok: bool, name: &str, requirement: &str) -> CalcResult<()> {
+    if ok {
+        Ok(())
+    } else {
+        Err(BadArgType(format!("`{}' needs {}", name, requirement)))
+    }
+}
+
+pub fn distribution(args: &Vec<ArgType>, env: &mut Environment, dist: Distribution,
+                    dfn: DistFn) -> CalcResult {
+    let name = if dfn == Density && dist.is_discrete() {
+        format!("{}-pmf", dist)
+    } else {
+        format!("{}-{}", dist, dfn)
+    };
+    let name = name.as_slice();
+
+    // samples are drawn by putting a random probability through the quantile
+    let drawing = dfn == Draw;
+    let given = if drawing { args.len() + 1 } else { args.len() };
+
+    let needed = match dist {
+        Normal if given == 1 => 1,
+        Normal | Binomial | Uniform | FisherF => 3,
+        Poisson | Exponential | StudentT | ChiSquared => 2,
+    };
+
+    if given != needed {
+        return Err(BadNumberOfArgs(name.to_string(), "only".to_string(),
+                                   if drawing { needed - 1 } else { needed }))
+    }
+
+    let mut params = Vec::new();
+    if drawing {
+        params.push(open_unit(env, fixed::bits_for(env.precision)));
+    }
+    for arg in args.iter() {
+        params.push(try!(exact_number(arg, env, name)));
+    }
+    if dist == Normal && params.len() == 1 {
+        params.push(num::zero());
+        params.push(num::one());
+    }
+
+    let zero: BigRational = num::zero();
+    let one_r: BigRational = num::one();
+    let x = params[0].clone();
+
+    match dist {
+        Normal => try!(check(params[2].is_positive(), name, "a positive standard deviation")),
+        Binomial => try!(check(params[1].is_integer() && !params[1].is_negative()
+                               && params[2] >= zero && params[2] <= one_r,
+                               name, "a whole number of trials and a probability")),
+        Poisson => try!(check(params[1].is_positive()
+                              && params[1] <= Ratio::from_integer(big(MAX_RATE)),
+                              name, format!("a positive rate of at most {}", MAX_RATE).as_slice())),
+        Exponential => try!(check(params[1].is_positive(), name, "a positive rate")),
+        Uniform => try!(check(params[1] < params[2], name, "a lower bound below the upper one")),
+        StudentT | ChiSquared | FisherF => try!(check(
+            params.tail().iter().all(|d| d.is_integer() && d.is_positive()),
+            name, "positive whole degrees of freedom")),
+    }
+
+    let n = match dist {
+        Binomial => try!(trials(&params[1], name)),
+        _ => 0
+    };
+
+    let dfn = if drawing { InverseCumulative } else { dfn };
+    if dfn == InverseCumulative {
+        let open_below = dist == Normal || dist == StudentT;
+        let open_above = match dist { Binomial | Uniform => false, _ => true };
+        try!(check(x >= zero && x <= one_r && !(open_below && x.is_zero())
+                   && !(open_above && x == one_r), name, "a probability in range"));
+    }
+
+    let digits = env.precision;
+    let p = fixed::bits_for(digits);
+    let q = p + GUARD_BITS;
+    let round = |v: Fix| fixed::to_rational(&v, p, digits);
+
+    let answer = match (dist, dfn) {
+        (Binomial, InverseCumulative) => {
+            let cdfs = binomial_cdfs(n, &params[2]);
+            let k = cdfs.iter().position(|c| *c >= x).unwrap_or(cdfs.len() - 1);
+            Ratio::from_integer(big(k))
+        },
+        (Binomial, Cumulative) => {
+            let cdfs = binomial_cdfs(n, &params[2]);
+            let last = cdfs.len() - 1;
+            if x < zero {
+                zero
+            } else {
+                let k = x.floor().to_integer().to_uint().unwrap_or(last);
+                cdfs[if k < last { k } else { last }].clone()
+            }
+        },
+        (Binomial, Density) => binomial_pmf(&x, n, &params[2]),
+        (Poisson, InverseCumulative) => {
+            let (weight, r) = try!(poisson_weight(&params[1], p));
+            let target = fixed::from_rational(&x, r);
+            let mut total: Fix = Zero::zero();
+            let terms = poisson_terms(&params[1], |_, term| {
+                // past the mode, a term too small to add anything means the
+                // total is as near to 1 as the precision can tell
+                let scaled = scale(&weight, term);
+                total = total + scaled;
+                total < target && !scaled.is_zero()
+            });
+            Ratio::from_integer(big(terms.len()))
+        },
+        (Poisson, _) => if x < zero || (dfn == Density && !x.is_integer()) {
+            zero
+        } else {
+            let (weight, r) = try!(poisson_weight(&params[1], p));
+            let last = x.floor().to_integer();
+            // terms too small to show at this precision, which only come past
+            // the mode, change nothing, so a large x needn't be reached
+            let mut faded = false;
+            let terms = poisson_terms(&params[1], |k, term| {
+                faded = scale(&weight, term).is_zero();
+                big(k) <= last && !faded
+            });
+            let sum = if dfn == Density && faded {
+                zero
+            } else if dfn == Density {
+                terms.last().unwrap().clone()
+            } else {
+                terms.iter().fold(zero.clone(), |acc, t| acc + *t)
+            };
+            round(fixed::rescale(&scale(&weight, &sum), r, p))
+        },
+        (Uniform, _) => {
+            let (a, b) = (&params[1], &params[2]);
+            match dfn {
+                Density => if x < *a || x > *b { zero } else { (*b - *a).recip() },
+                Cumulative => if x < *a {
+                    zero
+                } else if x > *b {
+                    one_r
+                } else {
+                    (x - *a) / (*b - *a)
+                },
+                InverseCumulative | Draw => *a + x * (*b - *a),
+            }
+        },
+        (Exponential, Density) => if x.is_negative() {
+            zero
+        } else {
+            let density = scale(&try!(fixed::exp(&-(params[1] * x), q)), &params[1]);
+            round(fixed::rescale(&density, q, p))
+        },
+        (Exponential, Cumulative) => round(try!(exponential_cdf(&x, &params[1], p))),
+        (Exponential, InverseCumulative) => {
+            let quantile = scale(&try!(ln(&(one_r - x), q)), &-params[1].recip());
+            round(fixed::rescale(&quantile, q, p))
+        },
+        (Normal, Density) => round(try!(normal_pdf(&x, &params[1], &params[2], p))),
+        (Normal, Cumulative) => round(try!(normal_cdf(&x, &params[1], &params[2], p))),
+        (StudentT, Density) => round(try!(t_pdf(&x, &params[1], p))),
+        (StudentT, Cumulative) => round(try!(t_cdf(&x, &params[1], p))),
+        (ChiSquared, Density) => round(try!(chi_squared_pdf(&x, &params[1], p))),
+        (ChiSquared, Cumulative) => round(try!(chi_squared_cdf(&x, &params[1], p))),
+        (FisherF, Density) => round(try!(f_pdf(&x, &params[1], &params[2], p))),
+        (FisherF, Cumulative) => round(try!(f_cdf(&x, &params[1], &params[2], p))),
+        (_, InverseCumulative) => {
+            let quantile = try!(match dist {
+                Normal => invert(|y| normal_cdf(y, &params[1], &params[2], p), &x,
+                                 params[1] - params[2], params[1] + params[2], false, p),
+                StudentT => invert(|y| t_cdf(y, &params[1], p), &x,
+                                   -one_r, one_r.clone(), false, p),
+                ChiSquared => invert(|y| chi_squared_cdf(y, &params[1], p), &x,
+                                     zero.clone(), params[1].clone(), true, p),
+                _ => invert(|y| f_cdf(y, &params[1], &params[2], p), &x,
+                            zero.clone(), one_r.clone(), true, p),
+            });
+            fixed::round_rational(&quantile, digits)
+        },
+        (_, Draw) => fail!("Impossible!"),
+    };
+
+    Ok(Atom(BigNum(if drawing { fixed::round_rational(&answer, digits) } else { answer })))
+}
@@ -0,0 +1,365 @@
+//! Fixed point arithmetic on big integers. A value v is held as the integer
+//! v * 2^p for some working precision of p bits, which lets the series below
+//! be summed to any number of digits without the denominators of
+//! BigRationals exploding.
+
+use std::num;
+use std::num::{Zero, Signed};
+use super::super::num::Integer;
+use super::{BigRational, Ratio};
+use super::bigint::{BigInt, ToBigInt};
+use super::super::{CalcResult, BadArgType, BadFloatRange};
+
+pub type Fix = BigInt;
+
+/// Extra bits carried through every calculation to soak up rounding errors.
+pub static GUARD_BITS: uint = 32;
+
+/// Number of times an argument is halved before a series is summed.
+static REDUCE_BITS: uint = 8;
+
+pub fn big(x: uint) -> BigInt {
+    x.to_bigint().unwrap()
+}
+
+/// The working precision in bits needed for `digits` correct decimal digits.
+pub fn bits_for(digits: uint) -> uint {
+    // log2(10) < 3.33
+    digits * 333 / 100 + 1 + GUARD_BITS
+}
+
+/// Number of bits in the magnitude of a big integer.
+pub fn bit_len(x: &BigInt) -> uint {
+    x.abs().to_biguint().unwrap().bits()
+}
+
+/// The fixed point representation of one.
+pub fn one(p: uint) -> Fix {
+    big(1) << p
+}
+
+pub fn from_rational(x: &BigRational, p: uint) -> Fix {
+    (*x.numer() << p) / *x.denom()
+}
+
+/// The exact rational value of a fixed point number.
+pub fn exact(x: &Fix, p: uint) -> BigRational {
+    Ratio::new(x.clone(), one(p))
+}
+
+/// Rounds a fixed point number to the nearest rational with a denominator
+/// of 10^digits.
+pub fn to_rational(x: &Fix, p: uint, digits: uint) -> BigRational {
+    let ten: BigInt = num::pow(big(10), digits);
+    let rounded = (x.abs() * ten + (one(p) >> 1)) >> p;
+
+    if x.is_negative() {
+        Ratio::new(-rounded, ten)
+    } else {
+        Ratio::new(rounded, ten)
+    }
+}
+
+/// Rounds a rational to the nearest one with a denominator of 10^digits.
+pub fn round_rational(x: &BigRational, digits: uint) -> BigRational {
+    let p = bits_for(digits);
+    to_rational(&from_rational(x, p), p, digits)
+}
+
+/// Moves a fixed point number from precision `from` to precision `to`.
+pub fn rescale(x: &Fix, from: uint, to: uint) -> Fix {
+    if from > to {
+        *x >> (from - to)
+    } else {
+        *x << (to - from)
+    }
+}
+
+pub fn mul(a: &Fix, b: &Fix, p: uint) -> Fix {
+    (*a * *b) >> p
+}
+
+pub fn div(a: &Fix, b: &Fix, p: uint) -> Fix {
+    (*a << p) / *b
+}
+
+/// Divides two integers, rounding to the nearest integer. `b` must be positive.
+pub fn round_div(a: &BigInt, b: &BigInt) -> BigInt {
+    ((*a << 1) + *b).div_floor(&(*b << 1))
+}
+
+/// The largest integer whose square is no greater than `n`.
+pub fn isqrt(n: &BigInt) -> BigInt {
+    if n.is_zero() {
+        return Zero::zero()
+    }
+
+    let mut x = one(bit_len(n) / 2 + 1);
+    loop {
+        let y = (x + *n / x) >> 1;
+        if y >= x {
+            return x
+        }
+        x = y;
+    }
+}
+
+/// The largest integer whose kth power is no greater than `n`, for n >= 0.
+pub fn iroot(n: &BigInt, k: uint) -> BigInt {
+    if n.is_zero() || k == 1 {
+        return n.clone()
+    }
+
+    let k_big = big(k);
+    let mut x = one(bit_len(n) / k + 1);
+    loop {
+        let y = (x * big(k - 1) + *n / num::pow(x.clone(), k - 1)) / k_big;
+        if y >= x {
+            return x
+        }
+        x = y;
+    }
+}
+
+/// The kth root of a non-negative rational as a fixed point number.
+pub fn nth_root(x: &BigRational, k: uint, p: uint) -> Fix {
+    iroot(&((*x.numer() << (p * k)) / *x.denom()), k)
+}
+
+pub fn sqrt(x: &Fix, p: uint) -> CalcResult<Fix> {
+    if x.is_negative() {
+        return Err(BadArgType("Cannot take the square root of a negative number".to_string()))
+    }
+
+    Ok(isqrt(&(*x << p)))
+}
+
+/// atan(1/q) by its Taylor series.
+fn atan_inv(q: uint, p: uint) -> Fix {
+    let q = big(q);
+    let q_sq = q * q;
+    let mut power = one(p) / q;
+    let mut sum = power.clone();
+    let mut k = 1u;
+
+    loop {
+        power = power / q_sq;
+        let term = power / big(2 * k + 1);
+        if term.is_zero() {
+            return sum
+        }
+        sum = if k % 2 == 1 { sum - term } else { sum + term };
+        k += 1;
+    }
+}
+
+/// atanh(z) by its Taylor series. Converges quickly for |z| <= 1/3.
+fn atanh_series(z: &Fix, p: uint) -> Fix {
+    let z_sq = mul(z, z, p);
+    let mut power = z.clone();
+    let mut sum = z.clone();
+    let mut k = 1u;
+
+    loop {
+        power = mul(&power, &z_sq, p);
+        let term = power / big(2 * k + 1);
+        if term.is_zero() {
+            return sum
+        }
+        sum = sum + term;
+        k += 1;
+    }
+}
+
+/// atan(z) by its Taylor series. Converges quickly for |z| <= 1/5.
+fn atan_series(z: &Fix, p: uint) -> Fix {
+    let z_sq = mul(z, z, p);
+    let mut power = z.clone();
+    let mut sum = z.clone();
+    let mut k = 1u;
+
+    loop {
+        power = mul(&power, &z_sq, p);
+        let term = power / big(2 * k + 1);
+        if term.is_zero() {
+            return sum
+        }
+        sum = if k % 2 == 1 { sum - term } else { sum + term };
+        k += 1;
+    }
+}
+
+/// Pi, by Machin's formula.
+pub fn pi(p: uint) -> Fix {
+    let q = p + GUARD_BITS;
+    let pi = (atan_inv(5, q) << 4) - (atan_inv(239, q) << 2);
+    rescale(&pi, q, p)
+}
+
+/// The natural logarithm of two, as 2 atanh(1/3).
+pub fn ln2(p: uint) -> Fix {
+    let q = p + GUARD_BITS;
+    let third = one(q) / big(3);
+    rescale(&(atanh_series(&third, q) << 1), q, p)
+}
+
+/// e^x. The argument is reduced to x = k ln2 + r with |r| <= ln2 / 2, and r is
+/// halved a few more times before summing the Taylor series.
+pub fn exp(x: &BigRational, p: uint) -> CalcResult<Fix> {
+    let rough = x.to_integer() * big(3) / big(2);
+    let k_guess = match rough.to_int() {
+        Some(k) => k,
+        None => return Err(BadFloatRange)
+    };
+
+    let extra = if k_guess > 0 { k_guess as uint } else { 0 };
+    let q = p + extra + bit_len(&x.to_integer()) + REDUCE_BITS + GUARD_BITS;
+
+    let fx = from_rational(x, q);
+    let ln2 = ln2(q);
+    let k = round_div(&fx, &ln2);
+    let r = (fx - k * ln2) >> REDUCE_BITS;
+
+    let mut term = one(q);
+    let mut sum = one(q);
+    let mut n = 1u;
+    loop {
+        term = mul(&term, &r, q) / big(n);
+        if term.is_zero() {
+            break
+        }
+        sum = sum + term;
+        n += 1;
+    }
+
+    for _ in range(0, REDUCE_BITS) {
+        sum = mul(&sum, &sum, q);
+    }
+
+    let shift = match k.to_int() {
+        Some(k) => k,
+        None => return Err(BadFloatRange)
+    };
+
+    let q_shifted = q as int - shift;
+    if q_shifted < 0 {
+        Ok(sum << (p + (-q_shifted) as uint))
+    } else {
+        Ok(rescale(&sum, q_shifted as uint, p))
+    }
+}
+
+/// The natural logarithm. x is split into m * 2^k with 1/2 < m < 2, and
+/// ln(m) = 2 atanh((m - 1) / (m + 1)).
+pub fn ln(x: &BigRational, p: uint) -> CalcResult<Fix> {
+    let zero: BigRational = num::zero();
+    if *x <= zero {
+        return Err(BadArgType("Logarithms are only defined for positive numbers".to_string()))
+    }
+
+    let k = bit_len(x.numer()) as int - bit_len(x.denom()) as int;
+    let m = if k >= 0 {
+        Ratio::new(x.numer().clone(), *x.denom() << k as uint)
+    } else {
+        Ratio::new(*x.numer() << (-k) as uint, x.denom().clone())
+    };
+
+    let one_r: BigRational = num::one();
+    let z = (m - one_r) / (m + one_r);
+
+    let k_big = k.to_bigint().unwrap();
+    let q = p + bit_len(&k_big) + GUARD_BITS;
+    let ln_m = atanh_series(&from_rational(&z, q), q) << 1;
+
+    Ok(rescale(&(ln_m + k_big * ln2(q)), q, p))
+}
+
+/// Sine and cosine of x. The argument is reduced modulo 2 pi, halved, and then
+/// put back together with the double angle formulas.
+pub fn sin_cos(x: &BigRational, p: uint) -> (Fix, Fix) {
+    let q = p + bit_len(&x.to_integer()) + REDUCE_BITS + GUARD_BITS;
+
+    let fx = from_rational(x, q);
+    let two_pi = pi(q) << 1;
+    let turns = round_div(&fx, &two_pi);
+    let r = (fx - turns * two_pi) >> REDUCE_BITS;
+
+    let mut sin: Fix = Zero::zero();
+    let mut cos = one(q);
+    let mut term = one(q);
+    let mut n = 1u;
+    loop {
+        term = mul(&term, &r, q) / big(n);
+        if term.is_zero() {
+            break
+        }
+        match n % 4 {
+            0 => cos = cos + term,
+            1 => sin = sin + term,
+            2 => cos = cos - term,
+            _ => sin = sin - term,
+        }
+        n += 1;
+    }
+
+    for _ in range(0, REDUCE_BITS) {
+        let new_sin = mul(&sin, &cos, q) << 1;
+        cos = (mul(&cos, &cos, q) << 1) - one(q);
+        sin = new_sin;
+    }
+
+    (rescale(&sin, q, p), rescale(&cos, q, p))
+}
+
+/// The arctangent. Large arguments use atan(x) = pi/2 - atan(1/x), and the
+/// rest are halved twice with atan(x) = 2 atan(x / (1 + sqrt(1 + x^2))).
+pub fn atan(x: &BigRational, p: uint) -> Fix {
+    let zero: BigRational = num::zero();
+    let one_r: BigRational = num::one();
+
+    if *x < zero {
+        return -atan(&-x, p)
+    }
+
+    if *x > one_r {
+        return (pi(p) >> 1) - atan(&x.recip(), p)
+    }
+
+    let q = p + REDUCE_BITS + GUARD_BITS;
+    let mut z = from_rational(x, q);
+    for _ in range(0, 2u) {
+        let root = isqrt(&((one(q) + mul(&z, &z, q)) << q));
+        z = div(&z, &(one(q) + root), q);
+    }
+
+    rescale(&(atan_series(&z, q) << 2), q, p)
+}
+
+/// The Euler-Mascheroni constant, by the Brent-McMillan algorithm:
+/// gamma = A / B - ln n, where A and B are sums of (n^k / k!)^2 weighted by the
+/// harmonic numbers. The error is about e^(-4n).
+pub fn euler_gamma(p: uint) -> Fix {
+    let q = p + GUARD_BITS;
+    let n = p / 5 + 1;
+    let n_sq = big(n * n);
+
+    let ln_n = ln(&Ratio::from_integer(big(n)), q).unwrap();
+    let mut a = -ln_n;
+    let mut b = one(q);
+    let (mut u, mut v) = (a.clone(), b.clone());
+    let mut k = 1u;
+
+    loop {
+        let k_big = big(k);
+        b = b * n_sq / (k_big * k_big);
+        a = (a * n_sq / k_big + b) / k_big;
+        if a.is_zero() && b.is_zero() {
+            break
+        }
+        u = u + a;
+        v = v + b;
+        k += 1;
+    }
+
+    rescale(&div(&u, &v, q), q, p)
+}
@@ -0,0 +1,141 @@
+//! The incomplete gamma and beta functions that the continuous distributions
+//! are built on, as fixed point numbers. Shape parameters are positive
+//! multiples of one half, where the complete gamma function has a closed form
+//! in factorials and the square root of pi.
+
+use std::num;
+use std::num::{Zero, Signed};
+use super::{BigRational, Ratio};
+use super::super::CalcResult;
+use super::combinatorics::factorial;
+use super::fixed;
+use super::fixed::{Fix, GUARD_BITS, big, one, bit_len};
+
+/// Gamma(a) for a positive multiple of one half, as (r, k) where the value is
+/// r pi^(k / 2). Gamma(n) = (n - 1)! and Gamma(n + 1/2) = (2n)! sqrt(pi) / (4^n n!).
+pub fn half_gamma(a: &BigRational) -> (BigRational, uint) {
+    let twice = (*a * Ratio::from_integer(big(2))).to_integer().to_uint().unwrap();
+    let n = twice / 2;
+
+    if twice % 2 == 0 {
+        (Ratio::from_integer(factorial(n - 1)), 0)
+    } else {
+        (Ratio::new(factorial(2 * n), num::pow(big(4), n) * factorial(n)), 1)
+    }
+}
+
+pub fn ln_pi(p: uint) -> CalcResult<Fix> {
+    let q = p + GUARD_BITS;
+    fixed::ln(&fixed::exact(&fixed::pi(q), q), p)
+}
+
+/// ln Gamma(a) for a positive multiple of one half.
+pub fn ln_gamma(a: &BigRational, p: uint) -> CalcResult<Fix> {
+    let (r, k) = half_gamma(a);
+    let ln_r = try!(fixed::ln(&r, p));
+    if k == 0 { Ok(ln_r) } else { Ok(ln_r + (try!(ln_pi(p)) >> 1)) }
+}
+
+/// ln B(a, b) = ln Gamma(a) + ln Gamma(b) - ln Gamma(a + b).
+pub fn ln_beta(a: &BigRational, b: &BigRational, p: uint) -> CalcResult<Fix> {
+    Ok(try!(ln_gamma(a, p)) + try!(ln_gamma(b, p)) - try!(ln_gamma(&(*a + *b), p)))
+}
+
+pub fn exp_fixed(x: &Fix, p: uint) -> CalcResult<Fix> {
+    fixed::exp(&fixed::exact(x, p), p)
+}
+
+/// Multiplies a fixed point number by a rational.
+pub fn scale(x: &Fix, r: &BigRational) -> Fix {
+    *x * *r.numer() / *r.denom()
+}
+
+fn clamp(x: Fix, p: uint) -> Fix {
+    if x.is_negative() {
+        Zero::zero()
+    } else if x > one(p) {
+        one(p)
+    } else {
+        x
+    }
+}
+
+/// Sums 1 + t_1 + t_2 + ... where t_n = t_(n-1) ratio(n) and the terms are all
+/// positive. Also returns how many bits the integer part of the sum takes,
+/// which is how much more precision the factor in front of it will need.
+fn series(ratio: |uint| -> BigRational, p: uint) -> (Fix, uint) {
+    let mut term = one(p);
+    let mut sum = one(p);
+    let mut n = 1u;
+
+    loop {
+        term = scale(&term, &ratio(n));
+        if term.is_zero() {
+            break
+        }
+        sum = sum + term;
+        n += 1;
+    }
+
+    let extra = bit_len(&sum) - p;
+    (sum, extra)
+}
+
+/// e^ln_factor times a sum from `series`, with the factor worked out to enough
+/// bits that the product is good to p bits however small the factor is.
+fn weighted(sum: &Fix, extra: uint, ln_factor: |uint| -> CalcResult<Fix>,
+            p: uint) -> CalcResult<Fix> {
+    let q = p + extra;
+    let factor = try!(exp_fixed(&try!(ln_factor(q)), q));
+    Ok((factor * *sum) >> q)
+}
+
+/// The regularized lower incomplete gamma function P(a, x), by the series
+/// P(a, x) = x^a e^-x / Gamma(a + 1) sum x^n / ((a + 1) ... (a + n)).
+pub fn gamma_p(a: &BigRational, x: &BigRational, p: uint) -> CalcResult<Fix> {
+    if !x.is_positive() {
+        return Ok(Zero::zero())
+    }
+
+    let one_r: BigRational = num::one();
+    let q = p + GUARD_BITS;
+    let (sum, extra) = series(|n| *x / (*a + Ratio::from_integer(big(n))), q);
+
+    let answer = try!(weighted(&sum, extra, |r| {
+        Ok(scale(&try!(fixed::ln(x, r)), a) - fixed::from_rational(x, r)
+           - try!(ln_gamma(&(*a + one_r), r)))
+    }, q));
+
+    Ok(clamp(fixed::rescale(&answer, q, p), p))
+}
+
+/// The regularized incomplete beta function I_x(a, b), by the series
+/// I_x(a, b) = x^a (1 - x)^b / (a B(a, b)) sum (a + b)_n / (a + 1)_n x^n.
+/// Large x use I_x(a, b) = 1 - I_(1 - x)(b, a) so that the series converges.
+pub fn beta_i(a: &BigRational, b: &BigRational, x: &BigRational, p: uint) -> CalcResult<Fix> {
+    let one_r: BigRational = num::one();
+    let two: BigRational = Ratio::from_integer(big(2));
+
+    if !x.is_positive() {
+        return Ok(Zero::zero())
+    }
+    if *x >= one_r {
+        return Ok(one(p))
+    }
+    if *x > (*a + one_r) / (*a + *b + two) {
+        return Ok(one(p) - try!(beta_i(b, a, &(one_r - *x), p)))
+    }
+
+    let q = p + GUARD_BITS;
+    let (sum, extra) = series(|n| {
+        let m = Ratio::from_integer(big(n - 1));
+        (*a + *b + m) / (*a + one_r + m) * *x
+    }, q);
+
+    let answer = try!(weighted(&sum, extra, |r| {
+        Ok(scale(&try!(fixed::ln(x, r)), a) + scale(&try!(fixed::ln(&(one_r - *x), r)), b)
+           - try!(fixed::ln(a, r)) - try!(ln_beta(a, b, r)))
+    }, q));
+
+    Ok(clamp(fixed::rescale(&answer, q, p), p))
+}
@@ -4,7 +4,7 @@ extern crate types;
 
 use self::types::{Atom, SExpr};
 use super::super::{Expression, Evaluate};
-use super::{ArgType, CalcResult, Environment, BigRational, Ratio};
+use super::{ArgType, CalcResult, Environment, Scope, BigRational, Ratio};
 use super::bigint::*;
 use super::super::{LiteralType, BigNum, List, Proc, Symbol, Boolean};
 use super::special::range_getter;
@@ -12,10 +12,10 @@ use super::super::{BadArgType, BadNumberOfArgs};
 use std::iter::range_step;
 
 pub fn proc_getter(args: &Vec<ArgType>, 
-                   env: &mut Environment) -> CalcResult<(Vec<String>, Expression)> {
+                   env: &mut Environment) -> CalcResult<(Vec<String>, Expression, Scope)> {
         
     match args[0].clone() {
-        Atom(Proc(x, y)) => Ok((x.clone(), y.clone())),
+        Atom(Proc(x, y, z)) => Ok((x, y, z)),
         Atom(Symbol(x)) => proc_getter(&vec!(Atom(try!(env.lookup(&x)))), env),
         SExpr(x) => proc_getter(&vec!(try!(x.eval(env))), env),
         _ =>  Err(BadArgType(format!("Expected function but found {}", args[0])))
@@ -33,7 +33,7 @@ pub fn map(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
         return Err(BadNumberOfArgs("map".to_string(), "at least".to_string(), 2))
     }
 
-    let (names, func) = try!(proc_getter(args, env));
+    let (names, func, scope) = try!(proc_getter(args, env));
 
     if names.len() == 0 || names.len() != args.tail().len() {
         return Err(BadArgType("Wrong number of arguments for lists supplied".to_string()))
@@ -61,9 +61,9 @@ pub fn map(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
             temp.push(list_vec.as_slice()[y][x].clone());
         }
 
-        let mut child_env = Environment::new_frame(env);
+        let mut child_env = env.new_call_frame(&scope);
         for (name_key, list_val) in names.iter().zip(temp.iter()) {
-            child_env.symbols.insert(name_key.clone(), list_val.clone());
+            child_env.bind(name_key.clone(), list_val.clone());
         }
         result.push(try!(try!(func.eval(&mut child_env)).arg_to_literal(env)));
     }
@@ -76,7 +76,7 @@ pub fn reduce(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
         return Err(BadNumberOfArgs("reduce".to_string(), "at least".to_string(), 3))
     }
 
-    let (names, fun) = try!(proc_getter(args, env));
+    let (names, fun, scope) = try!(proc_getter(args, env));
 
     let (x, y) = if names.len() != 2 {
         return Err(BadArgType("Expected 2 names".to_string()))
@@ -91,28 +91,28 @@ pub fn reduce(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
         _ => return Err(BadArgType("Invalid type for reduce".to_string()))
     };
 
-    Ok(Atom(try!(reduce_helper(x, y, &initval, list.as_slice(), env, &fun))))
+    Ok(Atom(try!(reduce_helper(x, y, &initval, list.as_slice(), env, &fun, &scope))))
 }
 
 pub type LitTy<T = LiteralType> = T;
 pub type Env<T = Environment> = T;
 
 pub fn reduce_helper(x: String, y: String, initval: &LitTy, list: &[LitTy], 
-                     env: &mut Env, fun: &Expression) -> CalcResult<LitTy> {
+                     env: &mut Env, fun: &Expression, scope: &Scope) -> CalcResult<LitTy> {
 
     if list.len() == 0 {
         return Err(BadArgType("Cannot fold empty lists!".to_string()))
     }
     
-    let mut child_env = Environment::new_frame(env);
+    let mut child_env = env.new_call_frame(scope);
 
-    child_env.symbols.insert(x.clone(), list[0].clone());
-    child_env.symbols.insert(y.clone(), initval.clone());
+    child_env.bind(x.clone(), list[0].clone());
+    child_env.bind(y.clone(), initval.clone());
     let mut result = try!(try!(fun.eval(&mut child_env)).arg_to_literal(env));
 
     for val in list.tail().iter() {
-        child_env.symbols.insert(x.clone(), val.clone());
-        child_env.symbols.insert(y.clone(), result.clone());
+        child_env.bind(x.clone(), val.clone());
+        child_env.bind(y.clone(), result.clone());
 
         result = try!(try!(fun.eval(&mut child_env)).arg_to_literal(env));
     }
@@ -126,7 +126,7 @@ pub fn filter(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
         return Err(BadNumberOfArgs("filter".to_string(), "at least".to_string(), 3))
     }
 
-    let (names, func) = try!(proc_getter(args, env));
+    let (names, func, scope) = try!(proc_getter(args, env));
 
     if names.len() != 1 {
         return Err(BadArgType("Expected 1 name for predicate".to_string()))
@@ -137,12 +137,12 @@ pub fn filter(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
         _ => return Err(BadArgType("Invalid type for filter".to_string()))
     };
 
-    let mut child_env = Environment::new_frame(env);
+    let mut child_env = env.new_call_frame(&scope);
 
     let mut new_list: Vec<LiteralType> = Vec::new();
 
     for item in list.iter() {
-        child_env.symbols.insert(names[0].clone(), item.clone());
+        child_env.bind(names[0].clone(), item.clone());
 
         match try!(func.eval(&mut child_env)) {
             Atom(Boolean(true)) => new_list.push(item.clone()),
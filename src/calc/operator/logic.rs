@@ -2,61 +2,63 @@
 
 extern crate types;
 
-use self::types::sexpr::BuiltIn;
-use self::types::literal::{Boolean, BigNum};
-use self::types::operator::{RoundId, Logic, If, Even};
+use self::types::literal::{Boolean, BigNum, Float, Complex, to_float};
+use self::types::operator::{RoundId, Even, OrderEq, Lt, LtEq, Gt, GtEq};
 use super::super::{Evaluate, LiteralType, CalcResult, Environment, 
                    NonBoolean, BadNumberOfArgs, BadArgType};
-use super::{ArgType, Atom, SExpr, BigRational};
+use super::{ArgType, Atom, BigRational, Ratio};
 
 pub type Args<T = Vec<ArgType>> = T;
 pub type Env<T = Environment> = T;
 
-/// Loop through nested conditional statements until a non-conditional expression
-/// is reached.
-pub fn cond(args: &Args, env: &mut Env)  -> CalcResult {
-    let mut arguments = args.clone();
-
-    loop {
-        if args.len() != 3 {
-            return Err(BadNumberOfArgs("if".to_string(), "only".to_string(), 3))
-        }
+/// The branch of a conditional that its condition picks, left unevaluated so
+/// that it can be evaluated in tail position.
+pub fn branch(args: &Args, env: &mut Env) -> CalcResult<ArgType> {
+    if args.len() != 3 {
+        return Err(BadNumberOfArgs("if".to_string(), "only".to_string(), 3))
+    }
 
-        let condition = match try!(arguments[0].desymbolize(env)) {
-            Boolean(x)  => x,
-            _ => return Err(NonBoolean)
-        };
+    let condition = match try!(args[0].desymbolize(env)) {
+        Boolean(x)  => x,
+        _ => return Err(NonBoolean)
+    };
 
-        let result = if condition {
-            arguments[1].clone()
-        } else {
-            arguments[2].clone()
-        };
+    Ok(if condition { args[1].clone() } else { args[2].clone() })
+}
 
-        match result {
-            Atom(_) => return Ok(result),
-            SExpr(ref x) => {
-                if x.expr_type == BuiltIn(Logic(If)) {
-                    arguments = x.args.clone();
-                } else {
-                    return x.eval(env)
-                }
-            }
-        }
-    }
+pub fn cond(args: &Args, env: &mut Env)  -> CalcResult {
+    try!(branch(args, env)).eval(env)
 }
 
 pub type BR = BigRational;
 pub type LitTy = LiteralType;
 
-pub fn ordering(args: &Vec<ArgType>, env: &mut Env, comp: |LitTy, LitTy| -> bool) -> CalcResult {
+pub fn ordering(args: &Vec<ArgType>, env: &mut Env, ord: OrderEq) -> CalcResult {
     if args.len() != 2 {
         return Err(BadNumberOfArgs("Ordering".to_string(), "only".to_string(), 2))
     }
 
 
-    Ok(Atom(Boolean(comp(try!(args[0].desymbolize(env)),
-                         try!(args[1].desymbolize(env))))))
+    // exact numbers are compared with inexact ones as floats
+    let (a, b) = match (try!(args[0].desymbolize(env)), try!(args[1].desymbolize(env))) {
+        (Float(x), BigNum(ref y)) => (Float(x), Float(to_float(y))),
+        (BigNum(ref x), Float(y)) => (Float(to_float(x)), Float(y)),
+        pair => pair
+    };
+
+    // complex numbers can be equal or not, but aren't ordered
+    let ordered = match ord {
+        Lt | LtEq | Gt | GtEq => true,
+        _ => false
+    };
+    match (&a, &b) {
+        (&Complex(..), _) | (_, &Complex(..)) if ordered => {
+            return Err(BadArgType(format!("`{}' is not defined for complex numbers", ord)))
+        },
+        _ => { }
+    }
+
+    Ok(Atom(Boolean(ord.to_ord()(a, b))))
 }
 
 pub fn and_or(args: &Args, env: &mut Env, short: bool) -> CalcResult {
@@ -118,6 +120,16 @@ pub fn num_op(args: &Args, env: &mut Env, op: RoundId) -> CalcResult {
 
     let num = match try!(args[0].desymbolize(env)) {
         BigNum(x) => x,
+        Float(x) => match op {
+            Round => return Ok(Atom(Float(x.round()))),
+            Floor => return Ok(Atom(Float(x.floor()))),
+            Ceiling => return Ok(Atom(Float(x.ceil()))),
+            Zero => return Ok(Atom(Boolean(x == 0.0))),
+            _ => match Ratio::from_float(x) {
+                Some(exact) => exact,
+                None => return Err(BadArgType(format!("Only finite numbers can {}", op.idea())))
+            }
+        },
         _ => return Err(BadArgType(format!("Only numbers can {}", op.idea())))
     };
 
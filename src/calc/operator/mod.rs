@@ -4,13 +4,17 @@ extern crate types;
 extern crate num;
 
 pub use self::num::bigint;
-pub use super::{BigRational, Ratio, CalcResult, Environment, ArgType, Atom, SExpr};
+pub use super::{BigRational, Ratio, CalcResult, Environment, Scope, ArgType, Atom, SExpr};
 pub use super::{LiteralType, Lit, LitRes, Symbol, Void};
 pub use super::literal::{cons, car, cdr, list};
 pub use self::types::operator::{OperatorType, Arithmetic, Transcend, Ordering,
                                 Pow, RoundIdent, Logic, Quote, Listings, ListOps,
-                                TransForms, XForms, Define, Lambda, Table, TableFromMatrix,
-                                Gate, MatrixStuff, MatrixOps, Help};
+                                TransForms, XForms, Define, Redefine, Lambda, Table, TableFromMatrix,
+                                Gate, MatrixStuff, MatrixOps, ComplexOps, Exactness,
+                                NumberTheory, PrimeOps, Combinatorics, Statistics, Regression,
+                                Probability, Randomness, Calculus, Solver, Series, Differentiate,
+                                Polynomials, Let, LetStar, LetRec, Begin, SetVar,
+                                Precision, Display, ToBase, Help};
 use super::matrice;
 
 pub mod special;
@@ -19,6 +23,21 @@ pub mod arithmetic;
 pub mod logic;
 pub mod listops;
 pub mod trig;
+pub mod fixed;
+pub mod complex;
+pub mod numtheory;
+pub mod primes;
+pub mod combinatorics;
+pub mod stats;
+pub mod regression;
+pub mod gamma;
+pub mod distributions;
+pub mod random;
+pub mod calculus;
+pub mod solve;
+pub mod series;
+pub mod polynomial;
+pub mod symbolic;
 
 pub fn list_ops(args: &Vec<ArgType>, env: &mut Environment, lop: ListOps) -> CalcResult {
     use self::types::operator::{List, Cons, Car, Cdr, Cadr, Cddr, Caddr, Cdddr};
@@ -72,17 +91,38 @@ pub fn eval(op_type: OperatorType, args: &Vec<ArgType>,
         Arithmetic(op) => arith(args, env, op),
         Pow => power::pow_wrapper(args, env),
         Transcend(op) => float_ops(args, env, op),
-        Ordering(ord) => ordering(args, env, ord.to_ord()),
+        Ordering(ord) => ordering(args, env, ord),
         RoundIdent(ri) => num_op(args, env, ri),
         Logic(gate) => handle_logic(args, env, gate),
-        Define  => super::define(args, env),
-        Lambda => Ok(Atom(Void)),
+        Define  => super::define(args, env, false),
+        Redefine => super::define(args, env, true),
+        Lambda => super::lambda(args, env),
+        Let | LetStar | LetRec => super::let_eval(args, env, op_type),
+        Begin => super::begin(args, env),
+        SetVar => super::set(args, env),
         Quote => Ok(Atom(Void)),
         Listings(lop) => list_ops(args, env, lop),
         TransForms(top) => transform_ops(args, env, top),
         Table => special::table(args, env),
         TableFromMatrix => special::table_from_matrix(args, env),
         MatrixStuff(mop) => matrice::matrix_ops(args, env, mop),
+        ComplexOps(cop) => complex::complex_ops(args, env, cop),
+        Exactness(eop) => arithmetic::exactness(args, env, eop),
+        NumberTheory(nop) => numtheory::number_theory(args, env, nop),
+        PrimeOps(pop) => primes::prime_ops(args, env, pop),
+        Combinatorics(cop) => combinatorics::combinatorics(args, env, cop),
+        Statistics(sop) => stats::stat_ops(args, env, sop),
+        Regression(fop) => regression::regression(args, env, fop),
+        Probability(dist, dfn) => distributions::distribution(args, env, dist, dfn),
+        Randomness(rop) => random::random_ops(args, env, rop),
+        Calculus(cop) => calculus::calculus(args, env, cop),
+        Solver(sop) => solve::solve(args, env, sop),
+        Series => series::series(args, env),
+        Differentiate => symbolic::d_dx(args, env),
+        Polynomials(pop) => polynomial::poly_ops(args, env, pop),
+        Precision => super::set_precision(args, env),
+        Display => super::set_display(args, env),
+        ToBase => arithmetic::to_base(args, env),
         Help => super::common::help(args),
     }
 }
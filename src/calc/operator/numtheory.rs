@@ -0,0 +1,297 @@
+//! Number theory on big integers.
+
+extern crate types;
+
+use std::mem;
+use std::num;
+use std::num::{Zero, Signed};
+use self::types::operator::{NumTheory, Gcd, Lcm, ModPow, ModInv, Crt, Totient, Divisors,
+                            Sigma, Jacobi};
+use self::types::literal::{BigNum, List};
+use super::super::num::Integer;
+use super::super::{CalcResult, Environment, Evaluate, LiteralType, BadArgType,
+                   BadNumberOfArgs};
+use super::{ArgType, Atom, Ratio};
+use super::bigint::{BigInt, ToBigInt};
+use super::fixed::big;
+use super::primes::factor;
+
+pub fn int_lit(x: BigInt) -> LiteralType {
+    BigNum(Ratio::from_integer(x))
+}
+
+/// The integer value of a literal, or an error naming the operator that needed it.
+pub fn lit_to_int(lit: &LiteralType, name: &str) -> CalcResult<BigInt> {
+    match lit {
+        &BigNum(ref x) if x.is_integer() => Ok(x.to_integer()),
+        x => Err(BadArgType(format!("`{}' only takes integers, but was given {}", name, x)))
+    }
+}
+
+fn arg_to_int(arg: &ArgType, env: &mut Environment, name: &str) -> CalcResult<BigInt> {
+    lit_to_int(&try!(arg.desymbolize(env)), name)
+}
+
+fn arg_to_ints(arg: &ArgType, env: &mut Environment, name: &str) -> CalcResult<Vec<BigInt>> {
+    match try!(arg.desymbolize(env)) {
+        List(ref xs) => {
+            let mut ints = Vec::new();
+            for x in xs.iter() {
+                ints.push(try!(lit_to_int(x, name)));
+            }
+            Ok(ints)
+        },
+        x => Err(BadArgType(format!("`{}' expected a list of integers, but was given {}",
+                                    name, x)))
+    }
+}
+
+fn positive(x: &BigInt, name: &str) -> CalcResult<()> {
+    if x.is_positive() {
+        Ok(())
+    } else {
+        Err(BadArgType(format!("`{}' is only defined for positive integers", name)))
+    }
+}
+
+/// Solves a x = g (mod m), where g = gcd(a, m), returning (g, x).
+pub fn ext_gcd(a: &BigInt, m: &BigInt) -> (BigInt, BigInt) {
+    let (mut old_r, mut r) = (a.mod_floor(m), m.clone());
+    let (mut old_s, mut s): (BigInt, BigInt) = (num::one(), num::zero());
+
+    while !r.is_zero() {
+        let q = old_r.div_floor(&r);
+        let next_r = old_r - q * r;
+        old_r = r;
+        r = next_r;
+        let next_s = old_s - q * s;
+        old_s = s;
+        s = next_s;
+    }
+
+    (old_r, old_s.mod_floor(m))
+}
+
+/// The inverse of a modulo m, if a and m are coprime.
+pub fn mod_inv(a: &BigInt, m: &BigInt) -> Option<BigInt> {
+    let one: BigInt = num::one();
+    let (g, x) = ext_gcd(a, m);
+    if g == one { Some(x) } else { None }
+}
+
+/// base^exp mod m by repeated squaring, for non-negative exp.
+pub fn mod_pow(base: &BigInt, exp: &BigInt, m: &BigInt) -> BigInt {
+    let one: BigInt = num::one();
+    let mut result = one.mod_floor(m);
+    let mut b = base.mod_floor(m);
+    let mut e = exp.clone();
+
+    while !e.is_zero() {
+        if e.is_odd() {
+            result = (result * b) % *m;
+        }
+        e = e >> 1;
+        b = (b * b) % *m;
+    }
+
+    result
+}
+
+/// Combines x = a_i (mod m_i) into a single congruence. The moduli need not be
+/// coprime, as long as the congruences agree.
+pub fn crt(residues: &Vec<BigInt>, moduli: &Vec<BigInt>) -> CalcResult<(BigInt, BigInt)> {
+    let (mut x, mut m): (BigInt, BigInt) = (num::zero(), num::one());
+
+    for (a, n) in residues.iter().zip(moduli.iter()) {
+        try!(positive(n, "crt"));
+        let g = m.gcd(n);
+        let diff = *a - x;
+        if !(diff % g).is_zero() {
+            return Err(BadArgType("The congruences given to `crt' have no solution".to_string()))
+        }
+
+        // x + m t = a (mod n), so t = (diff / g) (m / g)^-1 (mod n / g)
+        let n_g = *n / g;
+        let t = ((diff / g) * mod_inv(&(m / g), &n_g).unwrap()).mod_floor(&n_g);
+        x = x + m * t;
+        m = m * n_g;
+        x = x.mod_floor(&m);
+    }
+
+    Ok((x, m))
+}
+
+/// The Jacobi symbol (a / n) for odd positive n.
+pub fn jacobi(a: &BigInt, n: &BigInt) -> int {
+    let (three, four, five, eight) = (big(3), big(4), big(5), big(8));
+    let mut a = a.mod_floor(n);
+    let mut n = n.clone();
+    let mut result = 1;
+
+    while !a.is_zero() {
+        while a.is_even() {
+            a = a >> 1;
+            let r = n % eight;
+            if r == three || r == five {
+                result = -result;
+            }
+        }
+        mem::swap(&mut a, &mut n);
+        if a % four == three && n % four == three {
+            result = -result;
+        }
+        a = a % n;
+    }
+
+    if n == num::one() { result } else { 0 }
+}
+
+pub fn totient(factors: &Vec<(BigInt, uint)>) -> BigInt {
+    let mut phi: BigInt = num::one();
+    for &(ref p, e) in factors.iter() {
+        phi = phi * num::pow(p.clone(), e - 1) * (*p - num::one());
+    }
+    phi
+}
+
+pub fn divisors(factors: &Vec<(BigInt, uint)>) -> Vec<BigInt> {
+    let mut divs: Vec<BigInt> = vec![num::one()];
+    for &(ref p, e) in factors.iter() {
+        let mut next = Vec::new();
+        for d in divs.iter() {
+            let mut power = d.clone();
+            next.push(power.clone());
+            for _ in range(0, e) {
+                power = power * *p;
+                next.push(power.clone());
+            }
+        }
+        divs = next;
+    }
+    divs.sort();
+    divs
+}
+
+/// The sum of the kth powers of the divisors.
+pub fn sigma(factors: &Vec<(BigInt, uint)>, k: uint) -> BigInt {
+    let mut total: BigInt = num::one();
+    for &(ref p, e) in factors.iter() {
+        let pk = num::pow(p.clone(), k);
+        let (mut sum, mut term): (BigInt, BigInt) = (num::one(), num::one());
+        for _ in range(0, e) {
+            term = term * pk;
+            sum = sum + term;
+        }
+        total = total * sum;
+    }
+    total
+}
+
+pub fn number_theory(args: &Vec<ArgType>, env: &mut Environment, op: NumTheory) -> CalcResult {
+    let name = op.to_string();
+    let name = name.as_slice();
+
+    let (min, max) = match op {
+        Gcd | Lcm => (1, args.len()),
+        ModPow => (3, 3),
+        Totient | Divisors => (1, 1),
+        Sigma => (1, 2),
+        ModInv | Crt | Jacobi => (2, 2),
+    };
+
+    if args.len() < min || args.len() > max {
+        return Err(if min == max {
+            BadNumberOfArgs(name.to_string(), "only".to_string(), min)
+        } else if args.len() < min {
+            BadNumberOfArgs(name.to_string(), "at least".to_string(), min)
+        } else {
+            BadNumberOfArgs(name.to_string(), "at most".to_string(), max)
+        })
+    }
+
+    let answer = match op {
+        Gcd | Lcm => {
+            let mut acc = try!(arg_to_int(&args[0], env, name)).abs();
+            for arg in args.tail().iter() {
+                let x = try!(arg_to_int(arg, env, name));
+                acc = if op == Gcd {
+                    acc.gcd(&x)
+                } else if acc.is_zero() || x.is_zero() {
+                    // lcm divides by the gcd, which is zero when both are
+                    num::zero()
+                } else {
+                    acc.lcm(&x)
+                };
+            }
+            int_lit(acc)
+        },
+        ModPow => {
+            let base = try!(arg_to_int(&args[0], env, name));
+            let exp = try!(arg_to_int(&args[1], env, name));
+            let m = try!(arg_to_int(&args[2], env, name));
+            try!(positive(&m, name));
+
+            if exp.is_negative() {
+                match mod_inv(&base, &m) {
+                    Some(inv) => int_lit(mod_pow(&inv, &-exp, &m)),
+                    None => return Err(BadArgType(format!("{} has no inverse modulo {}",
+                                                          base, m)))
+                }
+            } else {
+                int_lit(mod_pow(&base, &exp, &m))
+            }
+        },
+        ModInv => {
+            let a = try!(arg_to_int(&args[0], env, name));
+            let m = try!(arg_to_int(&args[1], env, name));
+            try!(positive(&m, name));
+
+            match mod_inv(&a, &m) {
+                Some(inv) => int_lit(inv),
+                None => return Err(BadArgType(format!("{} has no inverse modulo {}", a, m)))
+            }
+        },
+        Crt => {
+            let residues = try!(arg_to_ints(&args[0], env, name));
+            let moduli = try!(arg_to_ints(&args[1], env, name));
+            if residues.len() != moduli.len() {
+                return Err(BadArgType("Mismatched lengths!".to_string()))
+            }
+            let (x, _) = try!(crt(&residues, &moduli));
+            int_lit(x)
+        },
+        Totient | Divisors | Sigma => {
+            let n = try!(arg_to_int(&args[0], env, name));
+            try!(positive(&n, name));
+            let factors = try!(factor(&n));
+
+            match op {
+                Totient => int_lit(totient(&factors)),
+                Divisors => List(divisors(&factors).move_iter().map(|d| int_lit(d)).collect()),
+                _ => {
+                    let k = if args.len() == 2 {
+                        match try!(arg_to_int(&args[1], env, name)).to_uint() {
+                            Some(k) => k,
+                            None => return Err(BadArgType(
+                                "The power given to `sigma' must be a non-negative integer"
+                                .to_string()))
+                        }
+                    } else {
+                        1
+                    };
+                    int_lit(sigma(&factors, k))
+                }
+            }
+        },
+        Jacobi => {
+            let a = try!(arg_to_int(&args[0], env, name));
+            let n = try!(arg_to_int(&args[1], env, name));
+            if !n.is_positive() || n.is_even() {
+                return Err(BadArgType("`jacobi' needs an odd positive modulus".to_string()))
+            }
+            int_lit(jacobi(&a, &n).to_bigint().unwrap())
+        },
+    };
+
+    Ok(Atom(answer))
+}
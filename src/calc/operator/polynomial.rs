@@ -0,0 +1,250 @@
+//! Polynomials with rational coefficients: building them from lists of
+//! coefficients, division, gcds, calculus, and their real roots. Roots that
+//! are rational are found exactly, and the rest to the working precision.
+
+extern crate types;
+
+use std::num;
+use std::num::{Zero, Signed};
+use self::types::operator::{PolyOp, MakePoly, PolyCoeffs, PolyEval, PolyDivRem, PolyGcd,
+                            PolyDeriv, PolyIntegral, PolyDegree, PolyRoots};
+use self::types::literal::{BigNum, Float, Complex, List, Polynomial, poly};
+use self::types::polynomial::{Coeffs, constant, degree, div_rem, gcd, derivative, integral,
+                              eval, monic, neg};
+use super::super::num::Integer;
+use super::super::{CalcResult, Environment, Evaluate, LiteralType, BadArgType,
+                   BadNumberOfArgs, DivByZero};
+use super::{ArgType, Atom, BigRational, Ratio};
+use super::bigint::BigInt;
+use super::fixed;
+use super::fixed::big;
+use super::numtheory::{int_lit, divisors};
+use super::primes::factor;
+use super::stats::lit_to_data;
+
+/// A real root, either found exactly or known to lie in an interval (lo, hi].
+enum Root {
+    Exact(BigRational),
+    Between(BigRational, BigRational),
+}
+
+fn half(x: &BigRational) -> BigRational {
+    *x / Ratio::from_integer(big(2))
+}
+
+pub fn get_poly(arg: &ArgType, env: &mut Environment, name: &str) -> CalcResult<Coeffs> {
+    match try!(arg.desymbolize(env)) {
+        Polynomial(c) => Ok(c),
+        BigNum(x) => Ok(constant(x)),
+        x => Err(BadArgType(format!("`{}' expected a polynomial, but was given {}", name, x)))
+    }
+}
+
+/// The Sturm sequence of a squarefree polynomial p: p, p', and then the
+/// negated remainder of each pair, down to a constant.
+fn sturm(p: &Coeffs) -> Vec<Coeffs> {
+    let mut seq = vec![p.clone(), derivative(p)];
+    loop {
+        let n = seq.len();
+        let (_, r) = div_rem(&seq[n - 2], &seq[n - 1]);
+        if r.len() == 0 {
+            break
+        }
+        seq.push(neg(&r));
+    }
+    seq
+}
+
+/// The number of sign changes along a Sturm sequence at x, skipping zeros.
+fn sign_changes(seq: &Vec<Coeffs>, x: &BigRational) -> uint {
+    let mut changes = 0u;
+    let mut last: Option<bool> = None;
+    for p in seq.iter() {
+        let y = eval(p, x);
+        if y.is_zero() {
+            continue
+        }
+        if last.map_or(false, |l| l != y.is_negative()) {
+            changes += 1;
+        }
+        last = Some(y.is_negative());
+    }
+    changes
+}
+
+/// The number of roots in (lo, hi], by Sturm's theorem.
+fn count(seq: &Vec<Coeffs>, lo: &BigRational, hi: &BigRational) -> uint {
+    sign_changes(seq, lo) - sign_changes(seq, hi)
+}
+
+/// Cauchy's bound, 1 + max |a_i / a_n|, which every root is strictly inside.
+fn root_bound(p: &Coeffs) -> BigRational {
+    let lead = p[p.len() - 1].clone();
+    let most = p.slice_to(p.len() - 1).iter().fold(Zero::zero(), |acc: BigRational, c| {
+        let size = (*c / lead).abs();
+        if size > acc { size } else { acc }
+    });
+    most + num::one()
+}
+
+/// Splits (-bound, bound] in halves until each piece holds one root of the
+/// squarefree p. The pieces come out in order.
+fn isolate(p: &Coeffs) -> Vec<(BigRational, BigRational)> {
+    let seq = sturm(p);
+    let hi = root_bound(p);
+    let lo = -hi;
+    let n = count(&seq, &lo, &hi);
+    let mut stack = vec![(lo, hi, n)];
+    let mut brackets = Vec::new();
+
+    loop {
+        let (lo, hi, n) = match stack.pop() {
+            Some(x) => x,
+            None => break
+        };
+        if n == 0 {
+            continue
+        }
+        if n == 1 {
+            brackets.push((lo, hi));
+            continue
+        }
+
+        // the ends of a piece are kept off the roots, so that the signs of p
+        // at them tell which side a root is on
+        let mut mid = half(&(lo + hi));
+        while eval(p, &mid).is_zero() {
+            mid = half(&(lo + mid));
+        }
+        let left = count(&seq, &lo, &mid);
+        stack.push((mid.clone(), hi, n - left));
+        stack.push((lo, mid, left));
+    }
+
+    brackets
+}
+
+/// Bisects a piece holding one root of p until it is no wider than `width',
+/// or until a bisection lands on the root.
+fn refine(p: &Coeffs, lo: BigRational, hi: BigRational, width: &BigRational) -> Root {
+    let (mut lo, mut hi) = (lo, hi);
+    let lo_negative = eval(p, &lo).is_negative();
+
+    while hi - lo > *width {
+        let mid = half(&(lo + hi));
+        let y = eval(p, &mid);
+        if y.is_zero() {
+            return Exact(mid)
+        }
+        if y.is_negative() == lo_negative {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    Between(lo, hi)
+}
+
+/// The distinct real roots of p in increasing order. A rational root a/q of p
+/// has q dividing the leading coefficient l of p with its denominators
+/// cleared, so once a root is in a piece narrower than 1/l, there is only one
+/// such fraction to try for each q.
+pub fn real_roots(p: &Coeffs, digits: uint) -> CalcResult<Vec<BigRational>> {
+    let (reduced, _) = div_rem(p, &gcd(p, &derivative(p)));
+    let squarefree = monic(&reduced);
+    if degree(&squarefree) == 0 {
+        return Ok(Vec::new())
+    }
+
+    let lead = squarefree.iter().fold(num::one::<BigInt>(), |acc, c| acc.lcm(c.denom()));
+    let denoms = divisors(&try!(factor(&lead)));
+    let tol = Ratio::new(big(1), num::pow(big(10), digits + 1));
+    let spacing = Ratio::new(big(1), lead);
+    let width = if tol < spacing { tol } else { spacing };
+
+    let mut roots = Vec::new();
+    for (lo, hi) in isolate(&squarefree).move_iter() {
+        match refine(&squarefree, lo, hi, &width) {
+            Exact(x) => roots.push(x),
+            Between(lo, hi) => {
+                let exact = denoms.iter().map(|q| {
+                    Ratio::new((hi * Ratio::from_integer(q.clone())).floor().to_integer(),
+                               q.clone())
+                }).find(|x| *x > lo && eval(&squarefree, x).is_zero());
+
+                roots.push(match exact {
+                    Some(x) => x,
+                    None => fixed::round_rational(&half(&(lo + hi)), digits)
+                });
+            }
+        }
+    }
+
+    Ok(roots)
+}
+
+/// The value of p at x, which may be any number or another polynomial, in
+/// which case this is their composition.
+fn evaluate(p: &Coeffs, x: &LiteralType) -> LiteralType {
+    p.iter().rev().fold(num::zero::<LiteralType>(), |acc, c| acc * *x + BigNum(c.clone()))
+}
+
+pub fn poly_ops(args: &Vec<ArgType>, env: &mut Environment, op: PolyOp) -> CalcResult {
+    let name = op.to_string();
+    let name = name.as_slice();
+
+    let needed = match op {
+        PolyEval | PolyDivRem | PolyGcd => 2,
+        _ => 1
+    };
+
+    if args.len() != needed {
+        return Err(BadNumberOfArgs(name.to_string(), "only".to_string(), needed))
+    }
+
+    if op == MakePoly {
+        // the coefficients come constant term first, as `polyfit' gives them
+        let coeffs = try!(lit_to_data(try!(args[0].desymbolize(env)), name));
+        return Ok(Atom(poly(coeffs)))
+    }
+
+    let p = try!(get_poly(&args[0], env, name));
+
+    let answer = match op {
+        PolyCoeffs => if p.len() == 0 {
+            List(vec![num::zero()])
+        } else {
+            List(p.move_iter().map(|c| BigNum(c)).collect())
+        },
+        PolyEval => match try!(args[1].desymbolize(env)) {
+            x @ BigNum(_) | x @ Float(_) | x @ Complex(_, _) | x @ Polynomial(_) => {
+                evaluate(&p, &x)
+            },
+            x => return Err(BadArgType(format!("`{}' can't evaluate at {}", name, x)))
+        },
+        PolyDivRem | PolyGcd => {
+            let q = try!(get_poly(&args[1], env, name));
+            if op == PolyGcd {
+                poly(gcd(&p, &q))
+            } else if q.len() == 0 {
+                return Err(DivByZero)
+            } else {
+                let (quot, rem) = div_rem(&p, &q);
+                List(vec![poly(quot), poly(rem)])
+            }
+        },
+        PolyDeriv => poly(derivative(&p)),
+        PolyIntegral => poly(integral(&p)),
+        PolyDegree => int_lit(big(degree(&p))),
+        PolyRoots => {
+            if p.len() == 0 {
+                return Err(BadArgType("Every number is a root of 0".to_string()))
+            }
+            List(try!(real_roots(&p, env.precision)).move_iter().map(|x| BigNum(x)).collect())
+        },
+        MakePoly => unreachable!(),
+    };
+
+    Ok(Atom(answer))
+}
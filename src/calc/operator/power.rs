@@ -3,52 +3,112 @@
 extern crate types;
 
 use std::num;
-use super::super::{BigNum, CalcResult, Environment, Evaluate, BadArgType,
-                   BadNumberOfArgs, BadPowerRange};
-use super::{BigRational, ArgType, Atom};
-use super::trig::float_ops;
-use self::types::operator::{Ln, Exp};
+use std::num::{Zero, Signed};
+use super::super::num::Integer;
+use super::super::{BigNum, Float, CalcResult, Environment, Evaluate, BadArgType,
+                   BadNumberOfArgs, BadPowerRange, DivByZero};
+use super::{BigRational, Ratio, ArgType, Atom};
+use super::bigint::BigInt;
+use self::types::literal::to_float;
+use super::{fixed, complex};
 
 pub fn pow_wrapper(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
     if args.len() != 2 {
         return Err(BadNumberOfArgs("pow".to_string(), "only".to_string(), 2))
-    } 
+    }
 
-    let (base, exponent) = match (try!(args[0].desymbolize(env)),
-                                  try!(args[1].desymbolize(env))) {
-        (BigNum(x), BigNum(y)) => (x, y),
-        _ => return Err(BadArgType("Only numbers can be raised to a power".to_string()))
-    };
+    let (base, exponent) = (try!(args[0].desymbolize(env)), try!(args[1].desymbolize(env)));
+
+    match (&base, &exponent) {
+        (&BigNum(ref x), &BigNum(ref y)) => {
+            if !(x.is_negative() && y.denom().is_even()) {
+                return Ok(Atom(BigNum(try!(pow(x, y, env.precision)))))
+            }
+        },
+        (&Float(x), &Float(y)) => return float_pow(x, y),
+        (&Float(x), &BigNum(ref y)) => return float_pow(x, to_float(y)),
+        (&BigNum(ref x), &Float(y)) => return float_pow(to_float(x), y),
+        _ => { }
+    }
 
-    Ok(Atom(BigNum(try!(pow(&base, &exponent, env)))))
+    match (complex::to_complex(&base), complex::to_complex(&exponent)) {
+        (Some(z), Some(w)) => Ok(Atom(complex::to_lit(try!(complex::pow(&z, &w,
+                                                                        env.precision))))),
+        _ => Err(BadArgType("Only numbers can be raised to a power".to_string()))
+    }
 }
 
-pub fn pow(base: &BigRational, exponent: &BigRational, 
-           env: &mut Environment) -> CalcResult<BigRational> {
+pub fn float_pow(base: f64, exponent: f64) -> CalcResult {
+    let answer = base.powf(exponent);
+    if answer.is_nan() {
+        Err(BadArgType(format!("{} cannot be raised to the power {}", base, exponent)))
+    } else {
+        Ok(Atom(Float(answer)))
+    }
+}
 
-    let power = match exponent.to_integer().to_u64() {
-        Some(x) => x,
-        None    => return Err(BadPowerRange)
-    };
+/// Raises a rational to a rational power. The result is exact whenever the
+/// root of the base is rational; otherwise it is correct to `digits` decimal
+/// places.
+pub fn pow(base: &BigRational, exponent: &BigRational,
+           digits: uint) -> CalcResult<BigRational> {
+    let zero: BigRational = num::zero();
+    let one: BigRational = num::one();
+
+    if exponent.is_zero() || *base == one {
+        return Ok(one)
+    }
+
+    if base.is_zero() {
+        return if exponent.is_negative() { Err(DivByZero) } else { Ok(zero) }
+    }
 
-    let maybe_radix = exponent - exponent.floor();
-    let root  = if maybe_radix == num::zero() {
-        num::one()
+    let base = if exponent.is_negative() { base.recip() } else { base.clone() };
+    let numer = exponent.numer().abs();
+
+    let powered = if base == -one {
+        if numer.is_even() { one } else { -one }
     } else {
-        let root_log = match try!(float_ops(&vec!(Atom(BigNum(base.clone()))), env, Ln)) {
-            Atom(BigNum(x)) => x,
-            _ => fail!("impossible")
-        };
-        let r_log_mul = root_log * maybe_radix;
-        match try!(float_ops(&vec!(Atom(BigNum(r_log_mul))), env, Exp)) {
-            Atom(BigNum(x)) => x,
-            _ => fail!("Impossible")
+        match numer.to_u64() {
+            Some(x) => exp_by_sq(&base, x),
+            None => return Err(BadPowerRange)
         }
     };
 
-    let powered = exp_by_sq(base, power);
+    match exponent.denom().to_uint() {
+        Some(1) => Ok(powered),
+        Some(root) => nth_root(&powered, root, digits),
+        None => Err(BadPowerRange)
+    }
+}
 
-    Ok(powered * root)
+/// The exact kth root of an integer, if it has one.
+pub fn exact_root(x: &BigInt, k: uint) -> Option<BigInt> {
+    let root = fixed::iroot(x, k);
+    if num::pow(root.clone(), k) == *x {
+        Some(root)
+    } else {
+        None
+    }
+}
+
+/// The kth root of a rational. Perfect powers give exact answers, and the
+/// rest are rounded to `digits` decimal places.
+pub fn nth_root(x: &BigRational, k: uint, digits: uint) -> CalcResult<BigRational> {
+    if x.is_negative() {
+        if k % 2 == 0 {
+            return Err(BadArgType(format!("{} has no real root of degree {}", x, k)))
+        }
+        return Ok(-try!(nth_root(&-x, k, digits)))
+    }
+
+    match (exact_root(x.numer(), k), exact_root(x.denom(), k)) {
+        (Some(numer), Some(denom)) => return Ok(Ratio::new(numer, denom)),
+        _ => { }
+    }
+
+    let p = fixed::bits_for(digits);
+    Ok(fixed::to_rational(&fixed::nth_root(x, k, p), p, digits))
 }
 
 pub fn exp_by_sq(base_orig: &BigRational, mut power: u64) -> BigRational {
@@ -65,5 +125,3 @@ pub fn exp_by_sq(base_orig: &BigRational, mut power: u64) -> BigRational {
 
     result
 }
-
-  
@@ -0,0 +1,404 @@
+//! Primality testing and factorization.
+
+extern crate types;
+
+use std::num::{Zero, Signed, ToStrRadix};
+use self::types::operator::{PrimeOp, IsPrime, NextPrime, PrevPrime, PrimesUpTo, Factor};
+use self::types::literal::{Boolean, List};
+use super::super::num::Integer;
+use super::super::{CalcResult, Environment, Evaluate, BadArgType, BadNumberOfArgs};
+use super::{ArgType, Atom};
+use super::bigint::{BigInt, ToBigInt};
+use super::fixed::{big, isqrt};
+use super::numtheory::{int_lit, lit_to_int, mod_pow, jacobi, ext_gcd};
+
+/// Primes below this are found by trial division before anything cleverer.
+static TRIAL_BOUND: uint = 1000;
+
+/// Numbers below this are checked with Miller-Rabin against the first thirteen
+/// primes as bases, which is known to be deterministic. About 3.3 * 10^24.
+static DETERMINISTIC_BOUND: &'static str = "3317044064679887385961981";
+
+static RHO_STEPS: uint = 100000;
+static RHO_TRIES: uint = 5;
+static ECM_CURVES: uint = 100;
+static ECM_BOUND: uint = 5000;
+
+/// The largest bound `primes-up-to' will sieve to.
+static SIEVE_LIMIT: uint = 10000000;
+
+/// The primes up to and including `limit`, by the sieve of Eratosthenes.
+pub fn sieve(limit: uint) -> Vec<uint> {
+    if limit < 2 {
+        return vec![]
+    }
+
+    let mut composite = Vec::from_elem(limit + 1, false);
+    let mut primes = Vec::new();
+
+    for i in range(2, limit + 1) {
+        if !composite[i] {
+            primes.push(i);
+            let mut j = i * i;
+            while j <= limit {
+                *composite.get_mut(j) = true;
+                j += i;
+            }
+        }
+    }
+
+    primes
+}
+
+/// Splits n into d * 2^s with d odd, for n > 0.
+fn split_twos(n: &BigInt) -> (BigInt, uint) {
+    let mut d = n.clone();
+    let mut s = 0u;
+    while d.is_even() {
+        d = d >> 1;
+        s += 1;
+    }
+    (d, s)
+}
+
+/// The Miller-Rabin test of odd n > 2 to the base a.
+fn strong_probable_prime(n: &BigInt, a: &BigInt) -> bool {
+    let one = big(1);
+    let n1 = *n - one;
+    let (d, s) = split_twos(&n1);
+
+    let mut x = mod_pow(a, &d, n);
+    if x == one || x == n1 {
+        return true
+    }
+
+    for _ in range(1, s) {
+        x = (x * x) % *n;
+        if x == n1 {
+            return true
+        }
+        if x == one {
+            return false
+        }
+    }
+
+    false
+}
+
+/// Halves x modulo odd n.
+fn half_mod(x: &BigInt, n: &BigInt) -> BigInt {
+    let x = x.mod_floor(n);
+    if x.is_odd() { (x + *n) >> 1 } else { x >> 1 }
+}
+
+/// The strong Lucas probable prime test of odd n > 2, with parameters chosen by
+/// Selfridge's method.
+fn strong_lucas_probable_prime(n: &BigInt) -> bool {
+    let root = isqrt(n);
+    if root * root == *n {
+        return false
+    }
+
+    // the first D in 5, -7, 9, -11, ... with (D / n) = -1
+    let mut d: int = 5;
+    loop {
+        let d_big = d.to_bigint().unwrap();
+        match jacobi(&d_big, n) {
+            -1 => break,
+            0 if d_big.abs() != *n => return false,
+            _ => { }
+        }
+        d = if d > 0 { -d - 2 } else { -d + 2 };
+    }
+
+    let d_big = d.to_bigint().unwrap();
+    let q_big = ((1 - d) / 4).to_bigint().unwrap();
+    let (k, s) = split_twos(&(*n + big(1)));
+
+    // U_k and V_k with P = 1, by the binary method over the bits of k
+    let (mut u, mut v) = (big(1), big(1));
+    let mut qk = q_big.mod_floor(n);
+    let bits = k.to_str_radix(2);
+
+    for bit in bits.as_slice().chars().skip(1) {
+        u = (u * v).mod_floor(n);
+        v = (v * v - (qk << 1)).mod_floor(n);
+        qk = (qk * qk).mod_floor(n);
+        if bit == '1' {
+            let next_u = half_mod(&(u + v), n);
+            v = half_mod(&(d_big * u + v), n);
+            u = next_u;
+            qk = (qk * q_big).mod_floor(n);
+        }
+    }
+
+    if u.is_zero() || v.is_zero() {
+        return true
+    }
+
+    for _ in range(1, s) {
+        v = (v * v - (qk << 1)).mod_floor(n);
+        if v.is_zero() {
+            return true
+        }
+        qk = (qk * qk).mod_floor(n);
+    }
+
+    false
+}
+
+/// Miller-Rabin with fixed bases below 3.3 * 10^24, where it is exact, and the
+/// Baillie-PSW test above, which has no known counterexamples.
+pub fn is_prime(n: &BigInt) -> bool {
+    if *n < big(2) {
+        return false
+    }
+
+    for &p in sieve(TRIAL_BOUND).iter() {
+        let p = big(p);
+        if *n == p {
+            return true
+        }
+        if (*n % p).is_zero() {
+            return false
+        }
+    }
+
+    if *n < big(TRIAL_BOUND * TRIAL_BOUND) {
+        return true
+    }
+
+    let bound: BigInt = from_str(DETERMINISTIC_BOUND).unwrap();
+    if *n < bound {
+        sieve(41).iter().all(|&a| strong_probable_prime(n, &big(a)))
+    } else {
+        strong_probable_prime(n, &big(2)) && strong_lucas_probable_prime(n)
+    }
+}
+
+pub fn next_prime(n: &BigInt) -> BigInt {
+    if *n < big(2) {
+        return big(2)
+    }
+
+    let mut candidate = if n.is_even() { *n + big(1) } else { *n + big(2) };
+    while !is_prime(&candidate) {
+        candidate = candidate + big(2);
+    }
+    candidate
+}
+
+/// The largest prime less than n, if there is one.
+pub fn prev_prime(n: &BigInt) -> Option<BigInt> {
+    if *n <= big(2) {
+        return None
+    }
+    if *n == big(3) {
+        return Some(big(2))
+    }
+
+    let mut candidate = if n.is_even() { *n - big(1) } else { *n - big(2) };
+    while !is_prime(&candidate) {
+        candidate = candidate - big(2);
+    }
+    Some(candidate)
+}
+
+/// Pollard's rho with x -> x^2 + c. Returns a proper factor of n, if it finds one.
+fn pollard_rho(n: &BigInt, c: uint) -> Option<BigInt> {
+    let one = big(1);
+    let c = big(c);
+    let (mut x, mut y) = (big(2), big(2));
+
+    for _ in range(0, RHO_STEPS) {
+        x = (x * x + c) % *n;
+        y = (y * y + c) % *n;
+        y = (y * y + c) % *n;
+        let d = (x - y).abs().gcd(n);
+        if d == *n {
+            return None
+        }
+        if d != one {
+            return Some(d)
+        }
+    }
+
+    None
+}
+
+/// A point on an elliptic curve modulo n, with None as the point at infinity.
+/// Err holds a divisor of n found while inverting.
+type EcPoint = Result<Option<(BigInt, BigInt)>, BigInt>;
+
+fn ec_add(p: &Option<(BigInt, BigInt)>, q: &Option<(BigInt, BigInt)>,
+          a: &BigInt, n: &BigInt) -> EcPoint {
+    let (x1, y1, x2, y2) = match (p, q) {
+        (&None, _) => return Ok(q.clone()),
+        (_, &None) => return Ok(p.clone()),
+        (&Some((ref x1, ref y1)), &Some((ref x2, ref y2))) => (x1, y1, x2, y2)
+    };
+
+    let (numer, denom) = if x1 == x2 {
+        if ((*y1 + *y2) % *n).is_zero() {
+            return Ok(None)
+        }
+        (*x1 * *x1 * big(3) + *a, *y1 << 1)
+    } else {
+        (*y2 - *y1, *x2 - *x1)
+    };
+
+    let (g, inv) = ext_gcd(&denom, n);
+    if g != big(1) {
+        return Err(g)
+    }
+
+    let slope = (numer * inv).mod_floor(n);
+    let x3 = (slope * slope - *x1 - *x2).mod_floor(n);
+    let y3 = (slope * (*x1 - x3) - *y1).mod_floor(n);
+    Ok(Some((x3, y3)))
+}
+
+fn ec_mul(k: uint, p: &Option<(BigInt, BigInt)>, a: &BigInt, n: &BigInt) -> EcPoint {
+    let mut k = k;
+    let mut result = None;
+    let mut power = p.clone();
+
+    while k > 0 {
+        if k % 2 == 1 {
+            result = try!(ec_add(&result, &power, a, n));
+        }
+        k /= 2;
+        if k > 0 {
+            power = try!(ec_add(&power, &power, a, n));
+        }
+    }
+
+    Ok(result)
+}
+
+/// Lenstra's elliptic curve method, stage one only, on the curves
+/// y^2 = x^3 + a x + 1 through (0, 1).
+fn ecm(n: &BigInt) -> Option<BigInt> {
+    let primes = sieve(ECM_BOUND);
+
+    for curve in range(1, ECM_CURVES + 1) {
+        let a = big(curve);
+        let mut point = Some((big(0), big(1)));
+
+        for &p in primes.iter() {
+            let mut q = p;
+            while q * p <= ECM_BOUND {
+                q *= p;
+            }
+
+            match ec_mul(q, &point, &a, n) {
+                Ok(Some(next)) => point = Some(next),
+                Ok(None) => break,
+                Err(g) => if g == *n { break } else { return Some(g) }
+            }
+        }
+    }
+
+    None
+}
+
+/// A proper factor of a composite n, by Pollard's rho and then ECM.
+fn find_factor(n: &BigInt) -> CalcResult<BigInt> {
+    for c in range(1, RHO_TRIES + 1) {
+        match pollard_rho(n, c) {
+            Some(d) => return Ok(d),
+            None => { }
+        }
+    }
+
+    match ecm(n) {
+        Some(d) => Ok(d),
+        None => Err(BadArgType(format!("Could not factor {}", n)))
+    }
+}
+
+/// The prime factorization of n > 0 as (prime, exponent) pairs in order. Small
+/// primes are found by trial division, and the rest by Pollard's rho, with
+/// ECM for when rho takes too long.
+pub fn factor(n: &BigInt) -> CalcResult<Vec<(BigInt, uint)>> {
+    let mut n = n.clone();
+    let mut primes: Vec<BigInt> = Vec::new();
+
+    for &p in sieve(TRIAL_BOUND).iter() {
+        let p = big(p);
+        while (n % p).is_zero() {
+            n = n / p;
+            primes.push(p.clone());
+        }
+    }
+
+    let mut composites = if n > big(1) { vec![n] } else { vec![] };
+    while composites.len() > 0 {
+        let m = composites.pop().unwrap();
+        if is_prime(&m) {
+            primes.push(m);
+        } else {
+            let d = try!(find_factor(&m));
+            composites.push(m / d);
+            composites.push(d);
+        }
+    }
+
+    primes.sort();
+    let mut factors: Vec<(BigInt, uint)> = Vec::new();
+    for p in primes.move_iter() {
+        let repeated = match factors.last() {
+            Some(&(ref last, _)) => *last == p,
+            None => false
+        };
+
+        if repeated {
+            let (_, ref mut e) = *factors.mut_last().unwrap();
+            *e += 1;
+        } else {
+            factors.push((p, 1));
+        }
+    }
+
+    Ok(factors)
+}
+
+pub fn prime_ops(args: &Vec<ArgType>, env: &mut Environment, op: PrimeOp) -> CalcResult {
+    let name = op.to_string();
+
+    if args.len() != 1 {
+        return Err(BadNumberOfArgs(name, "only".to_string(), 1))
+    }
+
+    let n = try!(lit_to_int(&try!(args[0].desymbolize(env)), name.as_slice()));
+
+    let answer = match op {
+        IsPrime => Boolean(is_prime(&n)),
+        NextPrime => int_lit(next_prime(&n)),
+        PrevPrime => match prev_prime(&n) {
+            Some(p) => int_lit(p),
+            None => return Err(BadArgType(format!("There are no primes less than {}", n)))
+        },
+        PrimesUpTo => match n.to_uint() {
+            Some(limit) if limit > SIEVE_LIMIT => {
+                return Err(BadArgType(format!("`primes-up-to' only goes up to {}", SIEVE_LIMIT)))
+            },
+            Some(limit) => List(sieve(limit).move_iter().map(|p| int_lit(big(p))).collect()),
+            None if n.is_negative() => List(vec![]),
+            None => return Err(BadArgType(format!("`primes-up-to' only goes up to {}",
+                                                  SIEVE_LIMIT)))
+        },
+        Factor => {
+            if !n.is_positive() {
+                return Err(BadArgType("`factor' is only defined for positive integers"
+                                      .to_string()))
+            }
+            let factors = try!(factor(&n));
+            List(factors.move_iter().map(|(p, e)| {
+                List(vec![int_lit(p), int_lit(big(e))])
+            }).collect())
+        },
+    };
+
+    Ok(Atom(answer))
+}
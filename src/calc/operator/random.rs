@@ -0,0 +1,155 @@
+//! Random numbers, drawn from the generator every frame of an environment
+//! shares, so that `seed' makes a whole session reproducible.
+
+extern crate types;
+
+use std::num;
+use std::num::Zero;
+use std::rand::{Rng, SeedableRng};
+use self::types::operator::{RandomOp, Random, RandomInt, RandomRational, Shuffle, Sample, Seed};
+use self::types::literal::{BigNum, List};
+use super::super::num::Integer;
+use super::super::{CalcResult, Environment, Evaluate, LiteralType, BadArgType,
+                   BadNumberOfArgs};
+use super::{ArgType, Atom, BigRational, Ratio, Void};
+use super::bigint::{BigInt, RandBigInt};
+use super::fixed::big;
+use super::matrice::arg_to_uint;
+use super::numtheory::{int_lit, lit_to_int};
+
+/// A uniformly random integer with lo <= n < hi.
+pub fn int_below(env: &Environment, lo: &BigInt, hi: &BigInt) -> BigInt {
+    let mut rng = env.rng.lock();
+    rng.gen_bigint_range(lo, hi)
+}
+
+/// A uniformly random index below n.
+pub fn index_below(env: &Environment, n: uint) -> uint {
+    let mut rng = env.rng.lock();
+    rng.gen_range(0, n)
+}
+
+/// A uniformly random number in [0, 1) with `digits` decimal places.
+pub fn unit(env: &Environment, digits: uint) -> BigRational {
+    let scale = num::pow(big(10), digits);
+    Ratio::new(int_below(env, &Zero::zero(), &scale), scale)
+}
+
+/// A random number strictly between 0 and 1, the middle of one of 2^p equal
+/// slices of [0, 1), for turning into a sample with a quantile function.
+pub fn open_unit(env: &Environment, p: uint) -> BigRational {
+    let slices = big(1) << p;
+    Ratio::new((int_below(env, &Zero::zero(), &slices) << 1) + big(1), slices << 1)
+}
+
+/// Restarts the generator from a seed. The seed is spread over the generator's
+/// 128 bits of state with splitmix64, so nearby seeds give unrelated sequences.
+pub fn seed(env: &Environment, n: u64) {
+    let mut x = n;
+    let mut words = [0u32, ..4];
+
+    for i in range(0, 2u) {
+        x = x + 0x9E3779B97F4A7C15u64;
+        let mut z = x;
+        z = (z ^ (z >> 30)) * 0xBF58476D1CE4E5B9u64;
+        z = (z ^ (z >> 27)) * 0x94D049BB133111EBu64;
+        z = z ^ (z >> 31);
+        words[2 * i] = z as u32;
+        words[2 * i + 1] = (z >> 32) as u32;
+    }
+
+    // the generator can't start from all zeros
+    if words.iter().all(|w| *w == 0) {
+        words[0] = 1;
+    }
+
+    *env.rng.lock() = SeedableRng::from_seed(words);
+}
+
+/// Shuffles the first k places of a list with the Fisher-Yates algorithm, so
+/// that they are a uniformly random selection from the whole list.
+fn shuffle(list: &mut Vec<LiteralType>, k: uint, env: &Environment) {
+    let n = list.len();
+    for i in range(0, k) {
+        let j = i + index_below(env, n - i);
+        list.as_mut_slice().swap(i, j);
+    }
+}
+
+fn get_list(arg: &ArgType, env: &mut Environment, name: &str) -> CalcResult<Vec<LiteralType>> {
+    match try!(arg.desymbolize(env)) {
+        List(x) => Ok(x),
+        x => Err(BadArgType(format!("`{}' expected a list, but was given {}", name, x)))
+    }
+}
+
+pub fn random_ops(args: &Vec<ArgType>, env: &mut Environment, op: RandomOp) -> CalcResult {
+    let name = op.to_string();
+    let name = name.as_slice();
+
+    let needed = match op {
+        Random => 0,
+        Shuffle | Seed => 1,
+        RandomInt | Sample => 2,
+        RandomRational => 3,
+    };
+
+    if args.len() != needed {
+        return Err(BadNumberOfArgs(name.to_string(), "only".to_string(), needed))
+    }
+
+    let answer = match op {
+        Random => BigNum(unit(env, env.precision)),
+        RandomInt => {
+            let lo = try!(lit_to_int(&try!(args[0].desymbolize(env)), name));
+            let hi = try!(lit_to_int(&try!(args[1].desymbolize(env)), name));
+            if lo > hi {
+                return Err(BadArgType(format!("`{}' needs the lower bound first", name)))
+            }
+            int_lit(int_below(env, &lo, &(hi + big(1))))
+        },
+        RandomRational => {
+            let (lo, hi) = match (try!(args[0].desymbolize(env)), try!(args[1].desymbolize(env))) {
+                (BigNum(lo), BigNum(hi)) => (lo, hi),
+                (x, y) => return Err(BadArgType(format!("`{}' needs exact bounds, not {} and {}",
+                                                        name, x, y)))
+            };
+            let denom = try!(lit_to_int(&try!(args[2].desymbolize(env)), name));
+            if denom <= Zero::zero() {
+                return Err(BadArgType(format!("`{}' needs a positive denominator", name)))
+            }
+
+            let d = Ratio::from_integer(denom.clone());
+            let (first, last) = ((lo * d).ceil().to_integer(), (hi * d).floor().to_integer());
+            if first > last {
+                return Err(BadArgType(format!("There are no multiples of 1/{} from {} to {}",
+                                              denom, lo, hi)))
+            }
+            BigNum(Ratio::new(int_below(env, &first, &(last + big(1))), denom))
+        },
+        Shuffle | Sample => {
+            let mut list = try!(get_list(&args[0], env, name));
+            let k = if op == Sample {
+                let k = try!(arg_to_uint(try!(args[1].desymbolize(env))));
+                if k > list.len() {
+                    return Err(BadArgType(format!("Cannot sample {} items from a list of {}",
+                                                  k, list.len())))
+                }
+                k
+            } else {
+                list.len()
+            };
+
+            shuffle(&mut list, k, env);
+            list.truncate(k);
+            List(list)
+        },
+        Seed => {
+            let n = try!(lit_to_int(&try!(args[0].desymbolize(env)), name));
+            seed(env, n.mod_floor(&(big(1) << 64)).to_u64().unwrap());
+            Void
+        },
+    };
+
+    Ok(Atom(answer))
+}
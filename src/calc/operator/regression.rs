@@ -0,0 +1,169 @@
+//! Least squares fits of polynomials, kept exact for rational data.
+
+extern crate matrix;
+extern crate types;
+
+use std::num;
+use std::num::Zero;
+use self::matrix::Matrice;
+use self::types::MatrixErr;
+use self::types::operator::{FitOp, LinReg, PolyFit, LinRegFn, PolyFitFn, Add, Mul};
+use self::types::literal::{BigNum, List, Matrix, Proc};
+use self::types::Global;
+use super::super::{CalcResult, Environment, Evaluate, LiteralType, Expression, BuiltIn,
+                   BadArgType, BadNumberOfArgs};
+use super::{ArgType, Atom, SExpr, Symbol, BigRational, Arithmetic};
+use super::matrice::arg_to_uint;
+use super::stats::{Data, lit_to_data, mean};
+
+/// The points to fit, either as a list of xs and a list of ys, or as a matrix
+/// with a point in each row, like the ones `matrix-from-fn' makes.
+fn get_points(args: &[ArgType], env: &mut Environment, name: &str) -> CalcResult<(Data, Data)> {
+    let (xs, ys) = if args.len() == 1 {
+        match try!(args[0].desymbolize(env)) {
+            Matrix(m) => {
+                if m.cols() < 2 {
+                    return Err(BadArgType(format!("`{}' needs a column of xs and of ys", name)))
+                }
+                let xs = List(m.get_col(0).map(|x| x.clone()).collect());
+                let ys = List(m.get_col(m.cols() - 1).map(|y| y.clone()).collect());
+                (try!(lit_to_data(xs, name)), try!(lit_to_data(ys, name)))
+            },
+            x => return Err(BadArgType(format!("`{}' expected a matrix, but was given {}",
+                                               name, x)))
+        }
+    } else {
+        (try!(lit_to_data(try!(args[0].desymbolize(env)), name)),
+         try!(lit_to_data(try!(args[1].desymbolize(env)), name)))
+    };
+
+    if xs.len() != ys.len() {
+        return Err(BadArgType("Mismatched lengths!".to_string()))
+    }
+    Ok((xs, ys))
+}
+
+fn to_matrix(elems: Data, width: uint, height: uint) -> CalcResult<Matrice<BigRational>> {
+    match Matrice::from_vec(elems, width, height) {
+        Ok(x) => Ok(x),
+        Err(m) => Err(MatrixErr(m))
+    }
+}
+
+/// The coefficients of the least squares polynomial of the given degree,
+/// constant term first. They solve the normal equations (X^T X) c = X^T y,
+/// where X has the powers of each x in its rows.
+pub fn polyfit(xs: &Data, ys: &Data, degree: uint) -> CalcResult<Data> {
+    let terms = degree + 1;
+    if xs.len() < terms {
+        return Err(BadArgType(format!("A fit of degree {} needs at least {} points",
+                                      degree, terms)))
+    }
+
+    let mut powers = Vec::with_capacity(xs.len() * terms);
+    for x in xs.iter() {
+        let mut power: BigRational = num::one();
+        for _ in range(0, terms) {
+            powers.push(power.clone());
+            power = power * *x;
+        }
+    }
+
+    let design = try!(to_matrix(powers, terms, xs.len()));
+    let observed = try!(to_matrix(ys.clone(), 1, ys.len()));
+    let design_t = design.transpose();
+    let normal = design_t * design;
+
+    let singular = match normal.determinant() {
+        Some(det) => det.is_zero(),
+        None => true
+    };
+    let inverse = match normal.inverse() {
+        Some(x) if !singular => x,
+        _ => return Err(BadArgType(format!("Too few distinct xs for a fit of degree {}",
+                                           degree)))
+    };
+
+    Ok((inverse * (design_t * observed)).to_vec())
+}
+
+/// The value of the polynomial with these coefficients at x, by Horner's rule.
+pub fn evaluate(coeffs: &Data, x: &BigRational) -> BigRational {
+    coeffs.iter().rev().fold(num::zero(), |acc: BigRational, c| acc * *x + *c)
+}
+
+/// The coefficient of determination, 1 - SS_res / SS_tot. It is 1 when the ys
+/// are all the same, since the constant term then fits them exactly.
+pub fn r_squared(ys: &Data, residuals: &Data) -> BigRational {
+    let my = mean(ys);
+    let ss_res = residuals.iter().fold(num::zero(), |acc: BigRational, r| acc + *r * *r);
+    let ss_tot = ys.iter().fold(num::zero(), |acc: BigRational, y| {
+        acc + (*y - my) * (*y - my)
+    });
+
+    if ss_tot.is_zero() {
+        num::one()
+    } else {
+        num::one::<BigRational>() - ss_res / ss_tot
+    }
+}
+
+/// The fitted polynomial as a procedure of one variable, in Horner form so it
+/// can be handed to `map' or `table'.
+pub fn model(coeffs: &Data) -> LiteralType {
+    let var = "x".to_string();
+    let mut iter = coeffs.iter().rev();
+    let mut body = Expression::new(BuiltIn(Arithmetic(Add)),
+                                   vec![Atom(BigNum(iter.next().unwrap().clone()))]);
+
+    for c in iter {
+        let term = Expression::new(BuiltIn(Arithmetic(Mul)),
+                                   vec![Atom(Symbol(var.clone())), SExpr(body)]);
+        body = Expression::new(BuiltIn(Arithmetic(Add)),
+                               vec![Atom(BigNum(c.clone())), SExpr(term)]);
+    }
+
+    Proc(vec![var], body, Global)
+}
+
+pub fn regression(args: &Vec<ArgType>, env: &mut Environment, op: FitOp) -> CalcResult {
+    let name = op.to_string();
+    let name = name.as_slice();
+
+    let (min, max) = match op {
+        LinReg | LinRegFn => (1, 2),
+        PolyFit | PolyFitFn => (2, 3),
+    };
+
+    if args.len() < min || args.len() > max {
+        return Err(if args.len() < min {
+            BadNumberOfArgs(name.to_string(), "at least".to_string(), min)
+        } else {
+            BadNumberOfArgs(name.to_string(), "at most".to_string(), max)
+        })
+    }
+
+    let (points, degree) = match op {
+        LinReg | LinRegFn => (args.as_slice(), 1),
+        PolyFit | PolyFitFn => {
+            let last = args.len() - 1;
+            (args.slice_to(last), try!(arg_to_uint(try!(args[last].desymbolize(env)))))
+        }
+    };
+
+    let (xs, ys) = try!(get_points(points, env, name));
+    let coeffs = try!(polyfit(&xs, &ys, degree));
+
+    if op == LinRegFn || op == PolyFitFn {
+        return Ok(Atom(model(&coeffs)))
+    }
+
+    let residuals: Data = xs.iter().zip(ys.iter()).map(|(x, y)| {
+        *y - evaluate(&coeffs, x)
+    }).collect();
+    let r2 = r_squared(&ys, &residuals);
+
+    Ok(Atom(List(vec![List(coeffs.move_iter().map(|c| BigNum(c)).collect()),
+                      List(residuals.move_iter().map(|r| BigNum(r)).collect()),
+                      BigNum(r2)])))
+}
@@ -0,0 +1,159 @@
+//! Sums and products of a procedure over a range of integers, and infinite
+//! series. The ranges are walked one integer at a time rather than built as
+//! lists.
+
+extern crate types;
+
+use std::num;
+use std::num::{Zero, Signed};
+use self::types::operator::{StatOp, Sum, Lambda};
+use self::types::literal::{BigNum, Proc};
+use self::types::sexpr::{Expression, BuiltIn};
+use super::super::{CalcResult, Environment, Evaluate, BadNumberOfArgs, NoConvergence};
+use super::{ArgType, Atom, SExpr, Symbol, BigRational, Ratio};
+use super::calculus::{Function, richardson, GUARD_DIGITS};
+use super::fixed;
+use super::bigint::BigInt;
+use super::fixed::big;
+use super::numtheory::{int_lit, lit_to_int};
+
+/// The number of terms summed before the first extrapolation of a series.
+static FIRST_TERMS: uint = 8;
+
+/// The number of terms of an alternating series summed before giving up.
+static MAX_TERMS: uint = 500;
+static MIN_TERMS: uint = 4;
+
+/// Is this argument a procedure, rather than a number or a list? Only names
+/// and lambdas are looked at, so that nothing is evaluated twice.
+pub fn is_function(arg: &ArgType, env: &mut Environment) -> bool {
+    match *arg {
+        Atom(Proc(_, _, _)) => true,
+        SExpr(Expression { expr_type: BuiltIn(Lambda), .. }) => true,
+        Atom(Symbol(ref x)) => match env.lookup(x) {
+            Ok(Proc(_, _, _)) => true,
+            _ => false
+        },
+        _ => false
+    }
+}
+
+/// (sum f a b) or (product f a b), over the integers from a to b inclusive.
+/// An empty range gives 0 or 1.
+pub fn range_op(args: &Vec<ArgType>, env: &mut Environment, op: StatOp) -> CalcResult {
+    let name = op.to_string();
+    let name = name.as_slice();
+
+    if args.len() != 3 {
+        return Err(BadNumberOfArgs(name.to_string(), "only".to_string(), 3))
+    }
+
+    let mut f = try!(Function::new(args, env, name));
+    let first = try!(lit_to_int(&try!(args[1].desymbolize(env)), name));
+    let last = try!(lit_to_int(&try!(args[2].desymbolize(env)), name));
+
+    let mut total = if op == Sum { num::zero() } else { num::one() };
+    let mut k = first;
+    while k <= last {
+        let y = try!(f.apply(int_lit(k.clone())));
+        total = if op == Sum { total + y } else { total * y };
+        k = k + big(1);
+    }
+
+    Ok(Atom(total))
+}
+
+/// Partial sums of f(k) from k = start on, with each term rounded to `digits'
+/// places so that the sums stay small.
+struct Partials<'a> {
+    f: &'a mut Function,
+    k: BigInt,
+    sum: BigRational,
+    digits: uint,
+}
+
+impl<'a> Partials<'a> {
+    fn next(&mut self) -> CalcResult<BigRational> {
+        let y = try!(self.f.at(&Ratio::from_integer(self.k.clone())));
+        self.sum = self.sum + fixed::round_rational(&y, self.digits);
+        self.k = self.k + big(1);
+        Ok(self.sum.clone())
+    }
+}
+
+/// Extrapolates the partial sums over 8, 16, 32, ... terms to infinitely many
+/// as a series in 1/n, which suits sums like 1/k^2 whose tails shrink slowly.
+fn richardson_sum(sums: &mut Partials, tol: &BigRational) -> CalcResult<BigRational> {
+    let mut terms = 0u;
+    let mut sum: BigRational = Zero::zero();
+
+    richardson(|i| {
+        while terms < FIRST_TERMS << i {
+            sum = try!(sums.next());
+            terms += 1;
+        }
+        Ok(sum.clone())
+    }, 1, tol, "series")
+}
+
+/// Shanks transformations of the partial sums by Wynn's epsilon algorithm,
+/// which suits alternating series. Each new partial sum adds a diagonal to
+/// the epsilon table, e(k+1, n) = e(k-1, n+1) + 1 / (e(k, n+1) - e(k, n)),
+/// whose even columns hold the estimates. Successive estimates agree well
+/// before they are right, so they are held to a thousandth of the tolerance.
+fn wynn_sum(sums: &mut Partials, tol: &BigRational) -> CalcResult<BigRational> {
+    let close = *tol / Ratio::from_integer(big(1000));
+    let mut prev: Vec<BigRational> = Vec::new();
+    let mut last: Option<BigRational> = None;
+
+    for n in range(0, MAX_TERMS) {
+        let mut diagonal = vec![try!(sums.next())];
+        for j in range(1, prev.len() + 1) {
+            let diff = diagonal[j - 1] - prev[j - 1];
+            if diff.is_zero() {
+                break
+            }
+            let below: BigRational = if j >= 2 { prev[j - 2].clone() } else { Zero::zero() };
+            diagonal.push(fixed::round_rational(&(below + diff.recip()), sums.digits));
+        }
+
+        let estimate = diagonal[(diagonal.len() - 1) / 2 * 2].clone();
+        match last {
+            Some(ref x) if n >= MIN_TERMS && (estimate - *x).abs() <= close => {
+                return Ok(estimate)
+            },
+            _ => { }
+        }
+        last = Some(estimate);
+        prev = diagonal;
+    }
+
+    Err(NoConvergence("series".to_string(), MAX_TERMS))
+}
+
+/// The sum of f(k) for k = a, a + 1, ... Series whose first two terms differ
+/// in sign are taken to alternate, and are summed with Wynn's algorithm, and
+/// the rest are extrapolated with Richardson's.
+pub fn series(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
+    if args.len() != 2 {
+        return Err(BadNumberOfArgs("series".to_string(), "only".to_string(), 2))
+    }
+
+    let mut f = try!(Function::new(args, env, "series"));
+    let start = try!(lit_to_int(&try!(args[1].desymbolize(env)), "series"));
+    let tol = Ratio::new(big(1), num::pow(big(10), env.precision));
+    f.extra_precision(GUARD_DIGITS);
+
+    let first = try!(f.at(&Ratio::from_integer(start.clone())));
+    let second = try!(f.at(&Ratio::from_integer(start + big(1))));
+
+    let mut sums = Partials { f: &mut f, k: start, sum: Zero::zero(),
+                              digits: env.precision + GUARD_DIGITS };
+    let limit = if (first * second).is_negative() {
+        try!(wynn_sum(&mut sums, &tol))
+    } else {
+        try!(richardson_sum(&mut sums, &tol))
+    };
+
+    Ok(Atom(BigNum(fixed::round_rational(&limit, env.precision))))
+}
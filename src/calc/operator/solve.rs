@@ -0,0 +1,182 @@
+//! Roots and minima of procedures of one variable. The searches run on exact
+//! rationals, rounded to a few more digits than the working precision so that
+//! their denominators stay small, and an exact root is returned as it is.
+
+extern crate types;
+
+use std::num;
+use std::num::{Zero, Signed};
+use self::types::operator::{SolveOp, FindRoot, Newton, Minimize};
+use self::types::literal::BigNum;
+use super::super::{CalcResult, Environment, Evaluate, BadArgType, BadNumberOfArgs,
+                   NoConvergence};
+use super::{ArgType, Atom, BigRational, Ratio};
+use super::calculus::{Function, to_exact, get_tolerance, derivative, GUARD_DIGITS};
+use super::fixed;
+use super::fixed::{big, bits_for};
+
+/// The number of steps Newton's method takes before giving up.
+static MAX_STEPS: uint = 100;
+
+fn half(x: &BigRational) -> BigRational {
+    *x / Ratio::from_integer(big(2))
+}
+
+fn get_number(arg: &ArgType, env: &mut Environment, name: &str) -> CalcResult<BigRational> {
+    to_exact(try!(arg.desymbolize(env)), name)
+}
+
+/// A root of f between a and b by bisection, which needs f(a) and f(b) to have
+/// opposite signs but then can't fail.
+pub fn find_root(f: &mut Function, a: &BigRational, b: &BigRational,
+                 tol: &BigRational) -> CalcResult<BigRational> {
+    let (mut lo, mut hi) = (a.clone(), b.clone());
+    let (f_lo, f_hi) = (try!(f.at(&lo)), try!(f.at(&hi)));
+
+    if f_lo.is_zero() {
+        return Ok(lo)
+    }
+    if f_hi.is_zero() {
+        return Ok(hi)
+    }
+    if f_lo.signum() == f_hi.signum() {
+        return Err(BadArgType(format!("`find-root' needs f to change sign between {} and {}",
+                                      a, b)))
+    }
+
+    let lo_negative = f_lo.is_negative();
+    while (hi - lo).abs() > *tol {
+        let mid = half(&(lo + hi));
+        let f_mid = try!(f.at(&mid));
+        if f_mid.is_zero() {
+            return Ok(mid)
+        }
+        if f_mid.is_negative() == lo_negative {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    Ok(half(&(lo + hi)))
+}
+
+/// A root of f by Newton's method from x0, with the derivative found
+/// numerically. Each step is rounded to `digits' decimal places.
+pub fn newton(f: &mut Function, x0: &BigRational, tol: &BigRational,
+              digits: uint) -> CalcResult<BigRational> {
+    let slope_tol = Ratio::new(big(1), num::pow(big(10), digits / 2));
+    let mut x = x0.clone();
+
+    for _ in range(0, MAX_STEPS) {
+        let y = try!(f.at(&x));
+        if y.is_zero() {
+            return Ok(x)
+        }
+
+        let slope = try!(derivative(f, &x, 1, &slope_tol));
+        if slope.is_zero() {
+            return Err(BadArgType(format!("`newton' reached a flat point at {}", x)))
+        }
+
+        let next = fixed::round_rational(&(x - y / slope), digits);
+        if (next - x).abs() <= *tol {
+            return Ok(next)
+        }
+        x = next;
+    }
+
+    Err(NoConvergence("newton".to_string(), MAX_STEPS))
+}
+
+/// The point where f is least between a and b, by golden section search. This
+/// assumes f has a single minimum there; otherwise it finds a local one.
+pub fn minimize(f: &mut Function, a: &BigRational, b: &BigRational, tol: &BigRational,
+                digits: uint) -> CalcResult<BigRational> {
+    let one_r: BigRational = num::one();
+    let five: BigRational = Ratio::from_integer(big(5));
+
+    // 1 / phi = (sqrt 5 - 1) / 2
+    let p = bits_for(digits);
+    let sqrt5 = fixed::to_rational(&fixed::nth_root(&five, 2, p), p, digits);
+    let ratio = half(&(sqrt5 - one_r));
+    let inner = |lo: &BigRational, hi: &BigRational| {
+        fixed::round_rational(&(*hi - ratio * (*hi - *lo)), digits)
+    };
+    let outer = |lo: &BigRational, hi: &BigRational| {
+        fixed::round_rational(&(*lo + ratio * (*hi - *lo)), digits)
+    };
+
+    let (mut lo, mut hi) = if a < b { (a.clone(), b.clone()) } else { (b.clone(), a.clone()) };
+    let (mut x1, mut x2) = (inner(&lo, &hi), outer(&lo, &hi));
+    let (mut f1, mut f2) = (try!(f.at(&x1)), try!(f.at(&x2)));
+
+    while hi - lo > *tol {
+        if f1 < f2 {
+            hi = x2;
+            x2 = x1;
+            f2 = f1;
+            x1 = inner(&lo, &hi);
+            f1 = try!(f.at(&x1));
+        } else {
+            lo = x1;
+            x1 = x2;
+            f1 = f2;
+            x2 = outer(&lo, &hi);
+            f2 = try!(f.at(&x2));
+        }
+    }
+
+    Ok(half(&(lo + hi)))
+}
+
+pub fn solve(args: &Vec<ArgType>, env: &mut Environment, op: SolveOp) -> CalcResult {
+    let name = op.to_string();
+    let name = name.as_slice();
+
+    let (min, max) = match op {
+        Newton => (2, 3),
+        FindRoot | Minimize => (3, 4),
+    };
+
+    if args.len() < min || args.len() > max {
+        return Err(if args.len() < min {
+            BadNumberOfArgs(name.to_string(), "at least".to_string(), min)
+        } else {
+            BadNumberOfArgs(name.to_string(), "at most".to_string(), max)
+        })
+    }
+
+    let mut f = try!(Function::new(args, env, name));
+    let tol = try!(get_tolerance(args.slice_from(min), env, name));
+    let digits = env.precision + GUARD_DIGITS;
+
+    let answer = match op {
+        FindRoot => {
+            let a = try!(get_number(&args[1], env, name));
+            let b = try!(get_number(&args[2], env, name));
+            try!(find_root(&mut f, &a, &b, &tol))
+        },
+        Newton => {
+            let x0 = try!(get_number(&args[1], env, name));
+            f.extra_precision(GUARD_DIGITS);
+            try!(newton(&mut f, &x0, &tol, digits))
+        },
+        Minimize => {
+            let a = try!(get_number(&args[1], env, name));
+            let b = try!(get_number(&args[2], env, name));
+
+            // f is flat at a minimum, so telling values apart there takes
+            // twice as many digits as the point has
+            f.extra_precision(env.precision + GUARD_DIGITS);
+            try!(minimize(&mut f, &a, &b, &tol, digits))
+        },
+    };
+
+    // an exact root is kept as it is, and anything else is rounded
+    if op != Minimize && try!(f.at(&answer)).is_zero() {
+        Ok(Atom(BigNum(answer)))
+    } else {
+        Ok(Atom(BigNum(fixed::round_rational(&answer, env.precision))))
+    }
+}
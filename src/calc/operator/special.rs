@@ -6,7 +6,7 @@ use self::types::literal::{BigNum, List, Matrix, Symbol, Void, LiteralType};
 use super::super::{Expression, Evaluate};
 use super::listops::proc_getter;
 use super::super::{BadArgType, BadNumberOfArgs};
-use super::{Environment, CalcResult, ArgType, Atom};
+use super::{Environment, Scope, CalcResult, ArgType, Atom};
 use std::{iter, cmp};
 
 pub fn range_getter(arg: LiteralType) -> CalcResult<int> {
@@ -22,7 +22,7 @@ type Expr = Expression;
 pub type Table = Vec<(Vec<String>, String)>;
 type Lists = Vec<Vec<Lit>>;
 
-fn make_table(lists: Lists, names: Vec<String>, func: Expr, fun_str: String,
+fn make_table(lists: Lists, names: Vec<String>, func: Expr, scope: Scope, fun_str: String,
               env: &mut Env) -> CalcResult<(Table, Vec<uint>, uint)> {
     if lists.len() < 1 {
         fail!("make-table requires at least one list of variables")
@@ -33,7 +33,7 @@ fn make_table(lists: Lists, names: Vec<String>, func: Expr, fun_str: String,
     table.push((names.clone(), fun_str));
 
     for column in range(0, lists[0].len()) {
-        let mut child_env = Environment::new_frame(env);
+        let mut child_env = env.new_call_frame(&scope);
 
         let values: Vec<Lit> = lists.iter().map(|x| x[column].clone()).collect();
         let mut t_names: Vec<String> = Vec::with_capacity(values.len());
@@ -47,7 +47,7 @@ fn make_table(lists: Lists, names: Vec<String>, func: Expr, fun_str: String,
         }
 
         for (arg, val) in names.iter().zip(values.iter()) {
-            child_env.symbols.insert(arg.clone(), val.clone());
+            child_env.bind(arg.clone(), val.clone());
         }
 
         let result = try!(try!(func.eval(&mut child_env)).desymbolize(env)).to_string();
@@ -117,7 +117,7 @@ pub fn table(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
         return Err(BadNumberOfArgs("table".to_string(), "at least".to_string(), 2))
     }
 
-    let (names, func) = try!(proc_getter(args, env));
+    let (names, func, scope) = try!(proc_getter(args, env));
     if names.len() < 1 {
         return Err(BadArgType("At least one variable must be supplied".to_string()))
     }
@@ -143,7 +143,7 @@ pub fn table(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
         return Err(BadArgType("Each list of arguments must be the same length".to_string()))
     }
 
-    let (table, names_len, fn_len) = try!(make_table(lists, names, func, fun_str, env));
+    let (table, names_len, fn_len) = try!(make_table(lists, names, func, scope, fun_str, env));
 
     table_writer(table, names_len, fn_len);
     
@@ -164,7 +164,7 @@ pub fn table_from_matrix(args: &Vec<ArgType>, env: &mut Environment) -> CalcResu
         return Err(BadArgType("Expeted at least one variable".to_string()))
     }
 
-    let (names, func) = try!(proc_getter(&args.tail().to_owned(), env));
+    let (names, func, _) = try!(proc_getter(&args.tail().to_owned(), env));
 
     let fun_str = match args[1] {
         Atom(Symbol(ref x)) => x.clone(),
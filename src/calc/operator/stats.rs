@@ -0,0 +1,272 @@
+//! Statistics on lists of numbers. Everything stays exact except for square
+//! roots that aren't rational, which are evaluated to the working precision.
+
+extern crate types;
+
+use std::num;
+use std::num::Zero;
+use self::types::operator::{StatOp, Mean, Median, Mode, Variance, PVariance, StDev, PStDev,
+                            Quantile, Min, Max, Sum, Product, Covariance, Correlation, ZScore,
+                            WeightedMean};
+use self::types::literal::{BigNum, List};
+use super::super::{CalcResult, Environment, Evaluate, LiteralType, BadArgType,
+                   BadNumberOfArgs, DivByZero};
+use super::{ArgType, Atom, BigRational, Ratio};
+use super::fixed::big;
+use super::power::nth_root;
+use super::series::{is_function, range_op};
+use super::special::merge_sort;
+
+pub type Data = Vec<BigRational>;
+
+pub fn lit_to_data(lit: LiteralType, name: &str) -> CalcResult<Data> {
+    let list = match lit {
+        List(x) => x,
+        x => return Err(BadArgType(format!("`{}' expected a list, but was given {}", name, x)))
+    };
+
+    let mut data = Vec::new();
+    for x in list.move_iter() {
+        match x {
+            BigNum(y) => data.push(y),
+            y => return Err(BadArgType(format!("`{}' only works on exact numbers, not {}",
+                                               name, y)))
+        }
+    }
+    Ok(data)
+}
+
+/// The data an operator works on is either a single list, or its terms.
+fn get_data(args: &[ArgType], env: &mut Environment, name: &str) -> CalcResult<Data> {
+    if args.len() == 1 {
+        match try!(args[0].desymbolize(env)) {
+            List(x) => return lit_to_data(List(x), name),
+            x => return lit_to_data(List(vec![x]), name)
+        }
+    }
+
+    let mut terms = Vec::new();
+    for arg in args.iter() {
+        terms.push(try!(arg.desymbolize(env)));
+    }
+    lit_to_data(List(terms), name)
+}
+
+fn non_empty(data: &Data, min: uint, name: &str) -> CalcResult<()> {
+    if data.len() < min {
+        Err(BadArgType(format!("`{}' needs at least {} data points", name, min)))
+    } else {
+        Ok(())
+    }
+}
+
+fn count(n: uint) -> BigRational {
+    Ratio::from_integer(big(n))
+}
+
+pub fn sum(data: &Data) -> BigRational {
+    data.iter().fold(num::zero(), |acc: BigRational, x| acc + *x)
+}
+
+pub fn product(data: &Data) -> BigRational {
+    data.iter().fold(num::one(), |acc: BigRational, x| acc * *x)
+}
+
+pub fn mean(data: &Data) -> BigRational {
+    sum(data) / count(data.len())
+}
+
+fn sorted(data: &Data) -> CalcResult<Data> {
+    merge_sort(data.clone(), 8)
+}
+
+pub fn median(data: &Data) -> CalcResult<BigRational> {
+    let data = try!(sorted(data));
+    let n = data.len();
+    if n % 2 == 1 {
+        Ok(data[n / 2].clone())
+    } else {
+        Ok((data[n / 2 - 1] + data[n / 2]) / count(2))
+    }
+}
+
+/// The most common value, or the smallest of them if there is a tie.
+pub fn mode(data: &Data) -> CalcResult<BigRational> {
+    let data = try!(sorted(data));
+    let (mut best, mut best_count) = (data[0].clone(), 0u);
+    let mut i = 0u;
+
+    while i < data.len() {
+        let mut j = i;
+        while j < data.len() && data[j] == data[i] {
+            j += 1;
+        }
+        if j - i > best_count {
+            best = data[i].clone();
+            best_count = j - i;
+        }
+        i = j;
+    }
+
+    Ok(best)
+}
+
+/// The sum of (x - mean x)(y - mean y).
+fn co_deviation(xs: &Data, ys: &Data) -> BigRational {
+    let (mx, my) = (mean(xs), mean(ys));
+    xs.iter().zip(ys.iter()).fold(num::zero(), |acc: BigRational, (x, y)| {
+        acc + (*x - mx) * (*y - my)
+    })
+}
+
+/// The variance, dividing by n for the population and n - 1 for a sample.
+pub fn variance(data: &Data, sample: bool) -> BigRational {
+    let n = if sample { data.len() - 1 } else { data.len() };
+    co_deviation(data, data) / count(n)
+}
+
+pub fn covariance(xs: &Data, ys: &Data) -> BigRational {
+    co_deviation(xs, ys) / count(xs.len() - 1)
+}
+
+/// Linearly interpolates between the closest ranks, so that the 0 quantile is
+/// the minimum and the 1 quantile is the maximum.
+pub fn quantile(data: &Data, p: &BigRational) -> CalcResult<BigRational> {
+    let (zero, one): (BigRational, BigRational) = (num::zero(), num::one());
+    if *p < zero || *p > one {
+        return Err(BadArgType("Quantiles must be between 0 and 1".to_string()))
+    }
+
+    let data = try!(sorted(data));
+    let h = *p * count(data.len() - 1);
+    let lo = h.floor();
+    let i = lo.to_integer().to_uint().unwrap();
+
+    if i + 1 >= data.len() {
+        Ok(data[i].clone())
+    } else {
+        Ok(data[i] + (h - lo) * (data[i + 1] - data[i]))
+    }
+}
+
+pub fn sqrt(x: &BigRational, env: &Environment) -> CalcResult<BigRational> {
+    nth_root(x, 2, env.precision)
+}
+
+fn pair_data(args: &Vec<ArgType>, env: &mut Environment,
+             name: &str) -> CalcResult<(Data, Data)> {
+    if args.len() != 2 {
+        return Err(BadNumberOfArgs(name.to_string(), "only".to_string(), 2))
+    }
+
+    let xs = try!(lit_to_data(try!(args[0].desymbolize(env)), name));
+    let ys = try!(lit_to_data(try!(args[1].desymbolize(env)), name));
+    if xs.len() != ys.len() {
+        return Err(BadArgType("Mismatched lengths!".to_string()))
+    }
+    Ok((xs, ys))
+}
+
+pub fn stat_ops(args: &Vec<ArgType>, env: &mut Environment, op: StatOp) -> CalcResult {
+    let name = op.to_string();
+    let name = name.as_slice();
+
+    if args.len() == 0 {
+        return Err(BadNumberOfArgs(name.to_string(), "at least".to_string(), 1))
+    }
+
+    // (sum f a b) and (product f a b) run f over a range of integers
+    if (op == Sum || op == Product) && is_function(&args[0], env) {
+        return range_op(args, env, op)
+    }
+
+    let answer = match op {
+        Quantile => {
+            if args.len() != 2 {
+                return Err(BadNumberOfArgs(name.to_string(), "only".to_string(), 2))
+            }
+            let data = try!(lit_to_data(try!(args[0].desymbolize(env)), name));
+            try!(non_empty(&data, 1, name));
+            let p = match try!(args[1].desymbolize(env)) {
+                BigNum(p) => p,
+                x => return Err(BadArgType(format!("{} is not a valid quantile", x)))
+            };
+            try!(quantile(&data, &p))
+        },
+        ZScore => {
+            if args.len() != 2 {
+                return Err(BadNumberOfArgs(name.to_string(), "only".to_string(), 2))
+            }
+            let x = match try!(args[0].desymbolize(env)) {
+                BigNum(x) => x,
+                x => return Err(BadArgType(format!("`{}' only works on exact numbers, not {}",
+                                                   name, x)))
+            };
+            let data = try!(lit_to_data(try!(args[1].desymbolize(env)), name));
+            try!(non_empty(&data, 1, name));
+            let sd = try!(sqrt(&variance(&data, false), env));
+            if sd.is_zero() {
+                return Err(DivByZero)
+            }
+            (x - mean(&data)) / sd
+        },
+        Covariance | Correlation | WeightedMean => {
+            let (xs, ys) = try!(pair_data(args, env, name));
+            try!(non_empty(&xs, if op == WeightedMean { 1 } else { 2 }, name));
+
+            match op {
+                Covariance => covariance(&xs, &ys),
+                Correlation => {
+                    let spread = co_deviation(&xs, &xs) * co_deviation(&ys, &ys);
+                    if spread.is_zero() {
+                        return Err(DivByZero)
+                    }
+                    co_deviation(&xs, &ys) / try!(sqrt(&spread, env))
+                },
+                _ => {
+                    let total = sum(&ys);
+                    if total.is_zero() {
+                        return Err(DivByZero)
+                    }
+                    xs.iter().zip(ys.iter()).fold(num::zero(), |acc: BigRational, (x, w)| {
+                        acc + *x * *w
+                    }) / total
+                }
+            }
+        },
+        _ => {
+            let data = try!(get_data(args.as_slice(), env, name));
+            match op {
+                Sum => sum(&data),
+                Product => product(&data),
+                Variance | StDev => {
+                    try!(non_empty(&data, 2, name));
+                    let var = variance(&data, true);
+                    if op == Variance { var } else { try!(sqrt(&var, env)) }
+                },
+                PVariance | PStDev => {
+                    try!(non_empty(&data, 1, name));
+                    let var = variance(&data, false);
+                    if op == PVariance { var } else { try!(sqrt(&var, env)) }
+                },
+                _ => {
+                    try!(non_empty(&data, 1, name));
+                    match op {
+                        Mean => mean(&data),
+                        Median => try!(median(&data)),
+                        Mode => try!(mode(&data)),
+                        Min => data.iter().fold(data[0].clone(), |a, b| {
+                            if *b < a { b.clone() } else { a }
+                        }),
+                        Max => data.iter().fold(data[0].clone(), |a, b| {
+                            if *b > a { b.clone() } else { a }
+                        }),
+                        _ => fail!("Impossible!")
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(Atom(BigNum(answer)))
+}
@@ -0,0 +1,317 @@
+//! Symbolic derivatives of procedures. The body of a procedure is walked and a
+//! new one built from the rules of differentiation, simplified as it goes so
+//! that the result stays readable and cheap to evaluate.
+
+extern crate types;
+
+use std::num;
+use std::num::{Zero, One};
+use self::types::operator::{Add, Sub, Mul, Div, Begin, Transcendental, Log, Ln, Exp, Sin, Cos,
+                            Tan, ASin, ACos, ATan, SinH, CosH, TanH, ASinH, ACosH, ATanH};
+use self::types::sexpr::{Expression, BuiltIn, Function};
+use self::types::literal::{BigNum, Float, Proc};
+use super::super::{CalcResult, Environment, Evaluate, BadArgType, BadNumberOfArgs};
+use super::{OperatorType, ArgType, Atom, SExpr, Symbol, Lit, Ratio, Arithmetic, Transcend, Pow};
+use super::fixed::big;
+
+fn int(n: int) -> ArgType {
+    Atom(BigNum(Ratio::from_integer(num::from_int(n).unwrap())))
+}
+
+fn half() -> ArgType {
+    Atom(BigNum(Ratio::new(big(1), big(2))))
+}
+
+/// The value of an argument that is a plain number.
+fn number(a: &ArgType) -> Option<Lit> {
+    match *a {
+        Atom(ref x @ BigNum(_)) | Atom(ref x @ Float(_)) => Some(x.clone()),
+        _ => None
+    }
+}
+
+fn is(a: &ArgType, n: int) -> bool {
+    match (number(a), number(&int(n))) {
+        (Some(x), Some(y)) => x == y,
+        _ => false
+    }
+}
+
+fn call(op: OperatorType, args: Vec<ArgType>) -> ArgType {
+    SExpr(Expression::new(BuiltIn(op), args))
+}
+
+fn apply(f: Transcendental, u: &ArgType) -> ArgType {
+    call(Transcend(f), vec![u.clone()])
+}
+
+/// The arguments of a sum or product, so that nested ones can be flattened.
+fn parts(a: ArgType, op: OperatorType) -> Vec<ArgType> {
+    match a {
+        SExpr(Expression { expr_type: BuiltIn(x), args }) => if x == op {
+            args
+        } else {
+            vec![call(x, args)]
+        },
+        a => vec![a]
+    }
+}
+
+/// A sum with nested sums flattened, its numbers added up and zeros dropped.
+fn sum(terms: Vec<ArgType>) -> ArgType {
+    let mut constant: Lit = Zero::zero();
+    let mut rest = Vec::new();
+
+    for term in terms.move_iter() {
+        for x in parts(term, Arithmetic(Add)).move_iter() {
+            match number(&x) {
+                Some(n) => constant = constant + n,
+                None => rest.push(x)
+            }
+        }
+    }
+
+    if !constant.is_zero() {
+        rest.push(Atom(constant));
+    }
+    match rest.len() {
+        0 => int(0),
+        1 => rest.pop().unwrap(),
+        _ => call(Arithmetic(Add), rest)
+    }
+}
+
+/// A product with nested products flattened and its numbers multiplied
+/// together in front. Anything times zero is zero.
+fn product(factors: Vec<ArgType>) -> ArgType {
+    let one: Lit = One::one();
+    let mut constant = one.clone();
+    let mut rest = Vec::new();
+
+    for factor in factors.move_iter() {
+        for x in parts(factor, Arithmetic(Mul)).move_iter() {
+            match number(&x) {
+                Some(n) => constant = constant * n,
+                None => rest.push(x)
+            }
+        }
+    }
+
+    if constant.is_zero() {
+        return int(0)
+    }
+    if constant == -one && rest.len() > 0 {
+        return negate(product(rest))
+    }
+    if constant != one || rest.len() == 0 {
+        rest.insert(0, Atom(constant));
+    }
+    match rest.len() {
+        1 => rest.pop().unwrap(),
+        _ => call(Arithmetic(Mul), rest)
+    }
+}
+
+fn negate(a: ArgType) -> ArgType {
+    match number(&a) {
+        Some(n) => return Atom(-n),
+        None => { }
+    }
+
+    match a {
+        SExpr(Expression { expr_type: BuiltIn(Arithmetic(Sub)), ref args }) if args.len() == 1 => {
+            args[0].clone()
+        },
+        a => call(Arithmetic(Sub), vec![a])
+    }
+}
+
+fn difference(a: ArgType, b: ArgType) -> ArgType {
+    match (number(&a), number(&b)) {
+        (Some(x), Some(y)) => return Atom(x - y),
+        _ => { }
+    }
+
+    if is(&b, 0) {
+        a
+    } else if is(&a, 0) {
+        negate(b)
+    } else {
+        call(Arithmetic(Sub), vec![a, b])
+    }
+}
+
+fn quotient(a: ArgType, b: ArgType) -> ArgType {
+    match (number(&a), number(&b)) {
+        (Some(ref x), Some(ref y)) if !y.is_zero() => return Atom(*x / *y),
+        _ => { }
+    }
+
+    if is(&a, 0) || is(&b, 1) {
+        a
+    } else {
+        call(Arithmetic(Div), vec![a, b])
+    }
+}
+
+fn power(a: ArgType, b: ArgType) -> ArgType {
+    if is(&b, 0) {
+        int(1)
+    } else if is(&b, 1) {
+        a
+    } else {
+        call(Pow, vec![a, b])
+    }
+}
+
+fn square(a: &ArgType) -> ArgType {
+    power(a.clone(), int(2))
+}
+
+/// Does the expression involve the variable at all?
+fn depends(a: &ArgType, var: &String) -> bool {
+    match *a {
+        Atom(Symbol(ref x)) => x == var,
+        Atom(_) => false,
+        SExpr(ref e) => e.args.iter().any(|x| depends(x, var))
+    }
+}
+
+/// The derivative of an expression with respect to a variable. Other symbols
+/// are taken to be constants.
+pub fn derive(a: &ArgType, var: &String) -> CalcResult<ArgType> {
+    if !depends(a, var) {
+        return Ok(int(0))
+    }
+
+    let (op, args) = match *a {
+        SExpr(Expression { expr_type: BuiltIn(op), ref args }) => (op, args),
+        SExpr(Expression { expr_type: Function(ref f), .. }) => {
+            return Err(BadArgType(format!("`d/dx' can't see inside the procedure `{}'", f)))
+        },
+        // the only atom that depends on the variable is the variable itself
+        Atom(_) => return Ok(int(1))
+    };
+
+    let mut diffs = Vec::new();
+    for x in args.iter() {
+        diffs.push(try!(derive(x, var)));
+    }
+
+    let answer = match op {
+        // a body that is a lone variable is held in a `begin'
+        Begin if args.len() == 1 => diffs.pop().unwrap(),
+        Arithmetic(Add) => sum(diffs),
+        Arithmetic(Sub) => if args.len() == 1 {
+            negate(diffs.pop().unwrap())
+        } else {
+            let first = diffs.remove(0).unwrap();
+            difference(first, sum(diffs))
+        },
+        Arithmetic(Mul) => {
+            // the product rule, with each factor differentiated in turn
+            let terms = range(0, args.len()).map(|i| {
+                let mut factors = args.clone();
+                *factors.get_mut(i) = diffs[i].clone();
+                product(factors)
+            }).collect();
+            sum(terms)
+        },
+        Arithmetic(Div) => if args.len() == 1 {
+            quotient(negate(diffs.pop().unwrap()), square(&args[0]))
+        } else {
+            // (/ u a b ...) divides u by the product of the rest
+            let (u, du) = (args[0].clone(), diffs[0].clone());
+            let v = product(args.slice_from(1).to_vec());
+            if !depends(&v, var) {
+                quotient(du, v)
+            } else {
+                let dv = try!(derive(&v, var));
+                quotient(difference(product(vec![du, v.clone()]), product(vec![u, dv])),
+                         square(&v))
+            }
+        },
+        Pow if args.len() == 2 => {
+            let (u, w) = (&args[0], &args[1]);
+            let (du, dw) = (diffs[0].clone(), diffs[1].clone());
+            if !depends(w, var) {
+                product(vec![w.clone(), power(u.clone(), difference(w.clone(), int(1))), du])
+            } else if !depends(u, var) {
+                product(vec![a.clone(), apply(Ln, u), dw])
+            } else {
+                // u^w = exp(w ln u), so its derivative is u^w (w' ln u + w u' / u)
+                let rate = sum(vec![product(vec![dw, apply(Ln, u)]),
+                                    quotient(product(vec![w.clone(), du]), u.clone())]);
+                product(vec![a.clone(), rate])
+            }
+        },
+        Transcend(f) if args.len() == 1 => {
+            let u = &args[0];
+            let du = diffs.pop().unwrap();
+            let over = |x: ArgType| quotient(du.clone(), x);
+            let times = |x: ArgType| product(vec![x, du.clone()]);
+            let root = |x: ArgType| power(x, half());
+
+            match f {
+                Log => over(product(vec![u.clone(), apply(Ln, &int(10))])),
+                Ln => over(u.clone()),
+                Exp => times(a.clone()),
+                Sin => times(apply(Cos, u)),
+                Cos => negate(times(apply(Sin, u))),
+                Tan => over(square(&apply(Cos, u))),
+                ASin => over(root(difference(int(1), square(u)))),
+                ACos => negate(over(root(difference(int(1), square(u))))),
+                ATan => over(sum(vec![int(1), square(u)])),
+                SinH => times(apply(CosH, u)),
+                CosH => times(apply(SinH, u)),
+                TanH => over(square(&apply(CosH, u))),
+                ASinH => over(root(sum(vec![square(u), int(1)]))),
+                ACosH => over(root(difference(square(u), int(1)))),
+                ATanH => over(difference(int(1), square(u))),
+            }
+        },
+        _ => return Err(BadArgType(format!("`d/dx' can't differentiate `{}'", op)))
+    };
+
+    Ok(answer)
+}
+
+/// (d/dx f 'x) is the derivative of the procedure f with respect to its
+/// parameter x, as a new procedure. The parameter can be left out when f has
+/// only one.
+pub fn d_dx(args: &Vec<ArgType>, env: &mut Environment) -> CalcResult {
+    if args.len() < 1 || args.len() > 2 {
+        return Err(if args.len() < 1 {
+            BadNumberOfArgs("d/dx".to_string(), "at least".to_string(), 1)
+        } else {
+            BadNumberOfArgs("d/dx".to_string(), "at most".to_string(), 2)
+        })
+    }
+
+    let (params, body, scope) = match try!(args[0].desymbolize(env)) {
+        Proc(params, body, scope) => (params, body, scope),
+        x => return Err(BadArgType(format!("`d/dx' expected a procedure, but was given {}", x)))
+    };
+
+    let var = if args.len() == 2 {
+        match args[1] {
+            Atom(Symbol(ref x)) => x.clone(),
+            _ => return Err(BadArgType("`d/dx' needs a variable name, such as 'x".to_string()))
+        }
+    } else if params.len() == 1 {
+        params[0].clone()
+    } else {
+        return Err(BadArgType("`d/dx' needs to be told which parameter to use".to_string()))
+    };
+
+    if !params.contains(&var) {
+        return Err(BadArgType(format!("`{}' is not a parameter of the procedure", var)))
+    }
+
+    let body = match try!(derive(&SExpr(body), &var)) {
+        SExpr(x) => x,
+        x => Expression::new(BuiltIn(Arithmetic(Add)), vec![x])
+    };
+
+    Ok(Atom(Proc(params, body, scope)))
+}
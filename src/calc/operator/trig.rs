@@ -1,50 +1,169 @@
 //! Trigonometry
 
-extern crate num;
 extern crate types;
 
-use self::num::rational::Ratio;
+use std::num;
+use std::num::{Zero, Signed};
 use self::types::operator::{Sin, Cos, Tan, ASin, ACos, ATan, SinH, CosH, TanH,
                             ASinH, ACosH, ATanH, Log, Ln, Exp, Transcendental};
-use self::types::literal::BigNum;
+use self::types::literal::{BigNum, Float, Complex};
 use self::types::sexpr::{Atom, ArgType};
 use super::super::{CalcResult, Environment, Evaluate, BadNumberOfArgs,
-                   BigRational, BadArgType, BadFloatRange};
+                   BigRational, Ratio, BadArgType, BadFloatRange, DivByZero};
+use super::{fixed, complex};
+use super::fixed::{Fix, GUARD_BITS};
 
 pub fn float_ops(args: &Vec<ArgType>, env: &mut Environment, fop: Transcendental) -> CalcResult {
     if args.len() > 1 {
         return Err(BadNumberOfArgs(fop.to_string(), "only".to_string(), 1))
     }
 
-    let floated = match try!(args[0].desymbolize(env)) {
-        BigNum(ref x)   => try!(rational_to_f64(x)),
+    let z = match try!(args[0].desymbolize(env)) {
+        BigNum(x) => if complex::needs_complex(&x, fop) {
+            (x, num::zero())
+        } else {
+            return Ok(Atom(BigNum(try!(transcend(&x, fop, env.precision)))))
+        },
+        Complex(re, im) => (re, im),
+        Float(x) => return float_transcend(x, fop),
         _  => return Err(BadArgType("Only numbers can use trigonometric functions".to_string()))
     };
 
+    Ok(Atom(complex::to_lit(try!(complex::transcend(&z, fop, env.precision)))))
+}
+
+/// Evaluates a transcendental function of an inexact number.
+pub fn float_transcend(x: f64, fop: Transcendental) -> CalcResult {
     let answer = match fop {
-        Sin => floated.sin(),
-        Cos => floated.cos(),
-        Tan => floated.tan(),
-        ASin => floated.asin(),
-        ACos =>  floated.acos(),
-        ATan => floated.atan(),
-        SinH => floated.sinh(),
-        CosH => floated.cosh(),
-        TanH => floated.tanh(),
-        ASinH => floated.asinh(),
-        ACosH => floated.acosh(),
-        ATanH => floated.atanh(),
-        Log => floated.log10(),
-        Ln => floated.ln(),
-        Exp => floated.exp(),
+        Sin => x.sin(),
+        Cos => x.cos(),
+        Tan => x.tan(),
+        ASin => x.asin(),
+        ACos => x.acos(),
+        ATan => x.atan(),
+        SinH => x.sinh(),
+        CosH => x.cosh(),
+        TanH => x.tanh(),
+        ASinH => x.asinh(),
+        ACosH => x.acosh(),
+        ATanH => x.atanh(),
+        Log => x.log10(),
+        Ln => x.ln(),
+        Exp => x.exp(),
     };
-        
-    match Ratio::from_float(answer) {
-        Some(x) => Ok(Atom(BigNum(x))),
-        None => Err(BadFloatRange)
+
+    if answer.is_nan() {
+        Err(BadArgType(format!("`{}' is not defined for {}", fop, x)))
+    } else {
+        Ok(Atom(Float(answer)))
     }
 }
 
+/// Evaluates a transcendental function, correct to `digits` decimal places.
+pub fn transcend(x: &BigRational, fop: Transcendental, digits: uint) -> CalcResult<BigRational> {
+    let p = fixed::bits_for(digits);
+    let answer = try!(transcend_fixed(x, fop, p));
+
+    Ok(fixed::to_rational(&answer, p, digits))
+}
+
+fn domain_err(fop: Transcendental, x: &BigRational) -> CalcResult<Fix> {
+    Err(BadArgType(format!("`{}' is not defined for {}", fop, x)))
+}
+
+/// Evaluates a transcendental function as a fixed point number of `p` bits.
+pub fn transcend_fixed(x: &BigRational, fop: Transcendental, p: uint) -> CalcResult<Fix> {
+    let zero: BigRational = num::zero();
+    let one: BigRational = num::one();
+    let q = p + GUARD_BITS;
+
+    let answer = match fop {
+        Sin => {
+            let (sin, _) = fixed::sin_cos(x, p);
+            sin
+        },
+        Cos => {
+            let (_, cos) = fixed::sin_cos(x, p);
+            cos
+        },
+        Tan => {
+            let (sin, cos) = fixed::sin_cos(x, q);
+            if cos.is_zero() {
+                return Err(DivByZero)
+            }
+            fixed::rescale(&fixed::div(&sin, &cos, q), q, p)
+        },
+        ASin | ACos => {
+            if x.abs() > one {
+                return domain_err(fop, x)
+            }
+            let asin = if x.abs() == one {
+                let half_pi = fixed::pi(p) >> 1;
+                if x.is_negative() { -half_pi } else { half_pi }
+            } else {
+                let root = try!(fixed::sqrt(&fixed::from_rational(&(one - *x * *x), q), q));
+                let ratio = fixed::div(&fixed::from_rational(x, q), &root, q);
+                fixed::atan(&fixed::exact(&ratio, q), p)
+            };
+            if fop == ASin {
+                asin
+            } else {
+                (fixed::pi(p) >> 1) - asin
+            }
+        },
+        ATan => fixed::atan(x, p),
+        SinH | CosH | TanH => {
+            let pos = try!(fixed::exp(x, q));
+            let neg = try!(fixed::exp(&-x, q));
+            let answer = match fop {
+                SinH => (pos - neg) >> 1,
+                CosH => (pos + neg) >> 1,
+                _ => fixed::div(&(pos - neg), &(pos + neg), q),
+            };
+            fixed::rescale(&answer, q, p)
+        },
+        ASinH => {
+            let y = x.abs();
+            let root = try!(fixed::sqrt(&fixed::from_rational(&(y * y + one), q), q));
+            let inner = fixed::exact(&(fixed::from_rational(&y, q) + root), q);
+            let asinh = try!(fixed::ln(&inner, p));
+            if x.is_negative() { -asinh } else { asinh }
+        },
+        ACosH => {
+            if *x < one {
+                return domain_err(fop, x)
+            }
+            let root = try!(fixed::sqrt(&fixed::from_rational(&(*x * *x - one), q), q));
+            let inner = fixed::exact(&(fixed::from_rational(x, q) + root), q);
+            try!(fixed::ln(&inner, p))
+        },
+        ATanH => {
+            if x.abs() >= one {
+                return domain_err(fop, x)
+            }
+            try!(fixed::ln(&((one + *x) / (one - *x)), p)) >> 1
+        },
+        Log => {
+            if *x <= zero {
+                return domain_err(fop, x)
+            }
+            let ten: BigRational = Ratio::from_integer(fixed::big(10));
+            let ln_x = try!(fixed::ln(x, q));
+            let ln_10 = try!(fixed::ln(&ten, q));
+            fixed::rescale(&fixed::div(&ln_x, &ln_10, q), q, p)
+        },
+        Ln => {
+            if *x <= zero {
+                return domain_err(fop, x)
+            }
+            try!(fixed::ln(x, p))
+        },
+        Exp => try!(fixed::exp(x, p)),
+    };
+
+    Ok(answer)
+}
+
 pub fn rational_to_f64(big: &BigRational) -> CalcResult<f64> {
     let numer = match big.numer().to_f64() {
         Some(x) => x,
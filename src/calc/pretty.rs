@@ -1,7 +1,17 @@
 //!Pretty print just prints the "relevant" information for a result.
 
-use super::types::literal::{LiteralType, Symbol, Void};
-use super::{Environment, Evaluate, CalcResult};
+use std::num;
+use std::cmp;
+use std::num::{Zero, Signed, ToStrRadix};
+use super::num::Integer;
+use super::types::{DisplayMode, Fractions, Mixed, Decimal, Scientific, Engineering, Radix,
+                   Auto};
+use super::types::literal::{LiteralType, BigNum, Complex, List, Polynomial, Symbol,
+                            Void};
+use super::types::polynomial;
+use super::bigint::BigInt;
+use super::{BigRational, Ratio, Environment, Evaluate, CalcResult};
+use super::operator::fixed::big;
 
 pub fn pretty_print(result: &CalcResult, env: &Environment) -> String {
     let res = match result {
@@ -18,18 +28,207 @@ pub fn pretty_print(result: &CalcResult, env: &Environment) -> String {
         return "".to_string()
     }
 
-    pretty(&success, env)
+    pretty(&success, env).append(" ")
 }
 
 pub fn pretty(arg: &LiteralType, env: &Environment) -> String {
-    let s = match arg {
+    match arg {
         &Symbol(ref s) => match env.lookup(s) {
             Ok(x) => pretty(&x, env),
             Err(_) => s.to_string()
         },
+        &BigNum(ref x) => format_number(x, env.display, env.precision),
+        &Complex(ref re, ref im) => {
+            let imag = format_number(&im.abs(), env.display, env.precision);
+            if re.is_zero() {
+                format!("{}{}i", if im.is_negative() { "-" } else { "" }, imag)
+            } else {
+                format!("{}{}{}i", format_number(re, env.display, env.precision),
+                        if im.is_negative() { "-" } else { "+" }, imag)
+            }
+        },
+        &List(ref list) => {
+            let items: Vec<String> = list.iter().map(|x| pretty(x, env)).collect();
+            format!("[{}]", items.connect(", "))
+        },
+        &Polynomial(ref c) => {
+            polynomial::format(c, |x| format_number(x, env.display, env.precision))
+        },
         &Void => "".to_string(),
         x => x.to_string()
+    }
+}
+
+/// Writes a number in the given display mode. `precision` limits how long a
+/// repeating decimal can be before auto mode falls back to a fraction.
+pub fn format_number(x: &BigRational, mode: DisplayMode, precision: uint) -> String {
+    let sign = if x.is_negative() { "-" } else { "" };
+    let y = x.abs();
+
+    match mode {
+        Fractions => x.to_string(),
+        Mixed => {
+            let whole = x.trunc();
+            if whole.is_zero() || x.is_integer() {
+                x.to_string()
+            } else {
+                format!("{} {}", whole, (*x - whole).abs())
+            }
+        },
+        Decimal(digits) => match exact_decimal(&y, digits) {
+            Some(s) => sign.to_string().append(s.as_slice()),
+            None => format!("{}{}...", sign, fixed_decimal(&y, digits))
+        },
+        Scientific(digits) => sign.to_string().append(exponential(&y, digits, 1).as_slice()),
+        Engineering(digits) => sign.to_string().append(exponential(&y, digits, 3).as_slice()),
+        Radix(radix) => if x.is_integer() {
+            sign.to_string().append(radix_integer(&y.to_integer(), radix).as_slice())
+        } else {
+            x.to_string()
+        },
+        Auto => match exact_decimal(&y, precision) {
+            Some(s) => sign.to_string().append(s.as_slice()),
+            None => x.to_string()
+        },
+    }
+}
+
+/// Writes a non-negative integer in a base from 2 to 36, with the prefix that
+/// it would be typed in with, e.g. `0xff' or `36#zz'.
+pub fn radix_integer(x: &BigInt, radix: uint) -> String {
+    let prefix = match radix {
+        16 => "0x".to_string(),
+        8 => "0o".to_string(),
+        2 => "0b".to_string(),
+        10 => "".to_string(),
+        _ => format!("{}#", radix)
+    };
+
+    prefix.append(x.to_str_radix(radix).as_slice())
+}
+
+/// The number of digits before a decimal expansion of 1/denom starts repeating,
+/// and the length of the part that repeats, which is zero when the expansion
+/// terminates. Gives up if the period is longer than `limit`.
+fn decimal_period(denom: &BigInt, limit: uint) -> Option<(uint, uint)> {
+    let (one, two, five, ten) = (big(1), big(2), big(5), big(10));
+    let (mut m, mut twos, mut fives) = (denom.clone(), 0u, 0u);
+
+    while (m % two).is_zero() {
+        m = m / two;
+        twos += 1;
+    }
+    while (m % five).is_zero() {
+        m = m / five;
+        fives += 1;
+    }
+
+    let pre = cmp::max(twos, fives);
+    if m == one {
+        return Some((pre, 0))
+    }
+
+    // the period is the multiplicative order of 10 modulo m
+    let mut r = ten % m;
+    let mut period = 1u;
+    while r != one {
+        if period >= limit {
+            return None
+        }
+        r = (r * ten) % m;
+        period += 1;
+    }
+
+    Some((pre, period))
+}
+
+/// Writes a non-negative number as an exact decimal, with any repeating digits
+/// in parentheses, e.g. `0.1(6)' for 1/6. Returns None if more than `limit`
+/// digits would be needed after the point.
+pub fn exact_decimal(x: &BigRational, limit: uint) -> Option<String> {
+    let (pre, period) = match decimal_period(x.denom(), limit) {
+        Some(p) => p,
+        None => return None
     };
-    s.append(" ")
+
+    if pre + period > limit {
+        return None
+    }
+
+    let (whole, mut rem) = x.numer().div_rem(x.denom());
+    if pre + period == 0 {
+        return Some(whole.to_string())
+    }
+
+    let ten = big(10);
+    let mut digits = String::new();
+    for i in range(0, pre + period) {
+        if i == pre {
+            digits.push_char('(');
+        }
+        rem = rem * ten;
+        digits.push_str((rem / *x.denom()).to_string().as_slice());
+        rem = rem % *x.denom();
+    }
+    if period > 0 {
+        digits.push_char(')');
+    }
+
+    Some(format!("{}.{}", whole, digits))
+}
+
+fn pow10(n: int) -> BigRational {
+    let power = Ratio::from_integer(num::pow(big(10), n.abs() as uint));
+    if n < 0 { power.recip() } else { power }
+}
+
+/// Rounds x * 10^shift to the nearest integer, for non-negative x.
+fn scaled_round(x: &BigRational, shift: int) -> BigInt {
+    let half = Ratio::new(big(1), big(2));
+    (*x * pow10(shift) + half).floor().to_integer()
+}
+
+/// Puts a decimal point `digits' places from the right of an integer.
+fn point(n: &BigInt, digits: uint) -> String {
+    let mut s = n.to_string();
+    while s.len() <= digits {
+        s = "0".to_string().append(s.as_slice());
+    }
+
+    if digits == 0 {
+        s
+    } else {
+        let split = s.len() - digits;
+        format!("{}.{}", s.as_slice().slice_to(split), s.as_slice().slice_from(split))
+    }
+}
+
+/// A non-negative number rounded to `digits` places after the point.
+pub fn fixed_decimal(x: &BigRational, digits: uint) -> String {
+    point(&scaled_round(x, digits as int), digits)
+}
+
+/// Writes a non-negative number as m * 10^e, where e is a multiple of `step`
+/// and 1 <= m < 10^step, with `digits` places after the point of m.
+pub fn exponential(x: &BigRational, digits: uint, step: int) -> String {
+    if x.is_zero() {
+        return format!("{}e0", point(&Zero::zero(), digits))
+    }
+
+    // 10^e <= x < 10^(e + 1)
+    let mut magnitude = x.numer().to_string().len() as int - x.denom().to_string().len() as int;
+    if *x < pow10(magnitude) {
+        magnitude -= 1;
+    }
+
+    let mut e = magnitude.div_floor(&step) * step;
+    let mut mantissa = scaled_round(x, digits as int - e);
+
+    // rounding can carry into another digit, e.g. 9.99 -> 10.0
+    if mantissa >= num::pow(big(10), digits + step as uint) {
+        e += step;
+        mantissa = scaled_round(x, digits as int - e);
+    }
+
+    format!("{}e{}", point(&mantissa, digits), e)
 }
-            
@@ -8,7 +8,9 @@ pub use types::{CalcResult, Environment, BadToken, BadExpr, BadArgType,
                 Ratio, BigRational, Expression, ArgType, Atom, SExpr,
                 LiteralType};
 pub use types::{sexpr, operator, literal, ErrorKind};
-use types::literal::{BigNum, Boolean};
+use types::literal::{BigNum, Float, Boolean, complex};
+use types::bigint::BigInt;
+use std::num;
 
 use operator::OperatorType;
 pub use tokenize::{TokenStream, MaybeToken};
@@ -42,6 +44,12 @@ pub fn is_paren(expr: &str) -> MaybeToken<Token, ErrorKind> {
 
 pub fn is_op(expr: &str) -> MaybeToken<Token, ErrorKind> {
     let word = make_word(expr);
+
+    // a quote is a token of its own even when it is stuck to a symbol, as in 'x
+    if word.len() > 1 && word.as_slice().starts_with("'") {
+        return (Some(Ok(Operator(operator::Quote))), 1)
+    }
+
     match from_str::<OperatorType>(word.as_slice()) {
         Some(op)    => (Some(Ok(Operator(op))), word.len()),
         _           => (None, 0)
@@ -70,10 +78,63 @@ pub fn is_var(expr: &str) -> MaybeToken<Token, ErrorKind> {
 pub fn is_number(expr: &str) -> MaybeToken<Token, ErrorKind> {
     let word = make_word(expr);
 
-    match str_to_rational(word.as_slice()) {
-        Ok(num) => (Some(Ok(Literal(BigNum(num)))), word.len()),
+    // #i marks a number as inexact, and #e as exact, which is the default
+    if word.as_slice().starts_with("#i") {
+        return match from_str::<f64>(word.as_slice().slice_from(2)) {
+            Some(x) => (Some(Ok(Literal(Float(x)))), word.len()),
+            None => (None, 0)
+        }
+    }
+
+    let number = if word.as_slice().starts_with("#e") {
+        word.as_slice().slice_from(2)
+    } else {
+        word.as_slice()
+    };
+
+    match str_to_rational(number) {
+        Ok(num) => return (Some(Ok(Literal(BigNum(num)))), word.len()),
+        Err(_)  => { }
+    }
+
+    match str_to_complex(number) {
+        Ok((re, im)) => (Some(Ok(Literal(complex(re, im)))), word.len()),
         Err(_)  => (None, 0)
-    } 
+    }
+}
+
+/// Converts a string such as `3+4i', `-1/2i' or `2-1i' into its real and
+/// imaginary parts.
+pub fn str_to_complex(word: &str) -> CalcResult<(BigRational, BigRational)> {
+    if word.len() < 2 || !word.ends_with("i") {
+        return Err(BadArgType("Bad numeric encoding".to_string()))
+    }
+
+    let body = word.slice_to(word.len() - 1);
+    let mut split = None;
+    let mut last = ' ';
+    for (i, c) in body.char_indices() {
+        // the sign of an exponent, as in 1e-9, doesn't start the imaginary part
+        if i > 0 && (c == '+' || c == '-') && last != 'e' && last != 'E' {
+            split = Some(i);
+        }
+        last = c;
+    }
+
+    match split {
+        Some(i) => Ok((try!(str_to_rational(body.slice_to(i))),
+                       try!(imaginary_part(body.slice_from(i))))),
+        None => Ok((num::zero(), try!(imaginary_part(body))))
+    }
+}
+
+fn imaginary_part(word: &str) -> CalcResult<BigRational> {
+    match word {
+        "+" => Ok(num::one()),
+        "-" => Ok(-num::one::<BigRational>()),
+        _ if word.starts_with("+") => str_to_rational(word.slice_from(1)),
+        _ => str_to_rational(word)
+    }
 }
 
 /// Enumeration of ways to write numbers.
@@ -85,6 +146,10 @@ pub enum NumEncoding {
 
 /// Converts a string into a bigrational.
 pub fn str_to_rational(word: &str) -> CalcResult<BigRational> {
+    match str_to_radix(word) {
+        Some(x) => return x,
+        None => { }
+    }
 
     let number_type = get_num_encoding(word);
     match number_type {
@@ -93,20 +158,106 @@ pub fn str_to_rational(word: &str) -> CalcResult<BigRational> {
             None => Err(BadArgType("Bad numeric encoding".to_string()))
         },
 
-        NonFraction => {
-            let floated =  match from_str::<f64>(word) {
-                Some(x) => x,
-                None => return Err(BadArgType("Bad numeric encoding".to_string()))
-            };
-            Ok(Ratio::from_float(floated).unwrap())
-        },
+        NonFraction => str_to_decimal(word),
 
         Invalid     => Err(BadArgType("Bad numeric encoding".to_string()))
     }
 }
 
+/// Splits the sign off a number, returning whether it was negative.
+fn split_sign<'a>(word: &'a str) -> (bool, &'a str) {
+    if word.starts_with("-") {
+        (true, word.slice_from(1))
+    } else if word.starts_with("+") {
+        (false, word.slice_from(1))
+    } else {
+        (false, word)
+    }
+}
+
+/// Converts an integer written in another base, such as `0xff', `0o17',
+/// `0b101' or `36#zz', into a bigrational. Returns None if the word isn't
+/// written with a base at all.
+pub fn str_to_radix(word: &str) -> Option<CalcResult<BigRational>> {
+    let (negative, unsigned) = split_sign(word);
+
+    let (radix, digits) = if unsigned.starts_with("0x") || unsigned.starts_with("0X") {
+        (16, unsigned.slice_from(2))
+    } else if unsigned.starts_with("0o") || unsigned.starts_with("0O") {
+        (8, unsigned.slice_from(2))
+    } else if unsigned.starts_with("0b") || unsigned.starts_with("0B") {
+        (2, unsigned.slice_from(2))
+    } else {
+        match unsigned.find('#') {
+            Some(i) => match from_str::<uint>(unsigned.slice_to(i)) {
+                Some(r) if r >= 2 && r <= 36 => (r, unsigned.slice_from(i + 1)),
+                _ => return Some(Err(BadArgType("Bases run from 2 to 36".to_string())))
+            },
+            None => return None
+        }
+    };
+
+    if digits.len() == 0 || !digits.chars().all(|c| c.to_digit(radix).is_some()) {
+        return Some(Err(BadArgType(format!("Bad digits for base {}", radix))))
+    }
+
+    match num::from_str_radix::<BigInt>(digits, radix) {
+        Some(x) => {
+            let value = Ratio::from_integer(x);
+            Some(Ok(if negative { -value } else { value }))
+        },
+        None => Some(Err(BadArgType("Bad numeric encoding".to_string())))
+    }
+}
+
+/// Converts a decimal such as `-12.5' or `6.022e23' into the exact fraction it
+/// represents.
+pub fn str_to_decimal(word: &str) -> CalcResult<BigRational> {
+    let bad = Err(BadArgType("Bad numeric encoding".to_string()));
+
+    let (mantissa, exponent) = match word.find(|c: char| c == 'e' || c == 'E') {
+        Some(i) => match from_str::<int>(word.slice_from(i + 1).trim_left_chars('+')) {
+            Some(e) => (word.slice_to(i), e),
+            None => return bad
+        },
+        None => (word, 0)
+    };
+
+    let (negative, unsigned) = split_sign(mantissa);
+
+    let (whole, frac) = match unsigned.find('.') {
+        Some(i) => (unsigned.slice_to(i), unsigned.slice_from(i + 1)),
+        None => (unsigned, "")
+    };
+
+    if whole.len() + frac.len() == 0 || 
+        !whole.chars().chain(frac.chars()).all(|c| c.is_digit()) {
+        return bad
+    }
+
+    let digits = whole.to_string().append(frac);
+    let numer = match from_str::<BigInt>(digits.as_slice()) {
+        Some(x) => x,
+        None => return bad
+    };
+    let ten: BigInt = FromPrimitive::from_uint(10).unwrap();
+    let scale = exponent - frac.len() as int;
+    let power = Ratio::from_integer(num::pow(ten, scale.abs() as uint));
+    let value = if scale < 0 {
+        Ratio::from_integer(numer) / power
+    } else {
+        Ratio::from_integer(numer) * power
+    };
+
+    Ok(if negative { -value } else { value })
+}
+
 /// Determines if a number is represented as a fraction or not.
 pub fn get_num_encoding(num_str: &str) -> NumEncoding {
+    if num_str.len() == 0 {
+        return Invalid
+    }
+
     if num_str.slice_to(1) == "/" || num_str.slice_to(num_str.len() -1) == "/" { 
             return Invalid
     }
@@ -2,14 +2,15 @@
 
 extern crate types;
 
-use self::types::{ErrorKind, BadExpr, BadToken, BadArgType};
+use self::types::{ErrorKind, BadExpr, BadToken, BadArgType, Global};
 use super::{CalcResult, Environment, Expression, ArgType, Atom, SExpr, LiteralType};
 use super::{Literal, LParen, RParen, Operator, Variable, Token};
 use super::tokenize::TokenStream;
 use super::sexpr;
 use super::literal::{List, Symbol, Proc};
 use super::sexpr::{BuiltIn, Function, ExprType};
-use super::operator::{Define, Lambda, Quote, Help, OperatorType};
+use super::operator::{Define, Redefine, Lambda, Let, LetStar, LetRec, Begin, Quote, Help,
+                      OperatorType};
 
 pub type Env = Environment;
 pub type Expr = CalcResult<ArgType>;
@@ -57,32 +58,98 @@ pub fn get_symbols(tokens: &mut TokenStream<Token, ErrorKind>) -> CalcResult<Vec
     }
 }                
 
+/// The body of a `lambda' or `let', which is the rest of it. A body of several
+/// expressions is evaluated in order, as if it were in a `begin'.
+pub fn get_body(tokens: &mut TokenStream<Token, ErrorKind>, env: &mut Environment,
+                name: &str) -> CalcResult<ArgType> {
+    let mut body = Vec::new();
+    loop {
+        let expr = match try!(strip(tokens.next())) {
+            LParen => {
+                match tokens.rev(1) {
+                    Ok(()) => { },
+                    Err(()) => fail!("Unexpected truncation of expression")
+                }
+                try!(translate(tokens, env))
+            },
+            Variable(x) => Atom(Symbol(x)),
+            Literal(x) => Atom(x),
+            Operator(_) => return Err(BadToken(format!("Invalid body for {}!", name))),
+            RParen => break
+        };
+        body.push(expr);
+    }
+
+    match body.len() {
+        0 => Err(BadToken(format!("Empty body for {}!", name))),
+        1 => Ok(body.pop().unwrap()),
+        _ => Ok(SExpr(Expression::new(sexpr::BuiltIn(Begin), body)))
+    }
+}
+
 pub fn lambda(tokens: &mut TokenStream<Token, ErrorKind>, 
               env: &mut Environment) -> CalcResult<(Vec<String>, ArgType)> {
     
     let symbols = try!(get_symbols(tokens));
-    let body = match try!(strip(tokens.next())) {
-        LParen => {
-            match tokens.rev(1) {
-                Ok(()) => { },
-                Err(()) => fail!("Unexpected truncation of expression")
-            }
-            try!(translate(tokens, env))
-        },
-        Variable(x) => Atom(Symbol(x)),
-        Literal(x) => Atom(x),
-        Operator(_) => return Err(BadToken("Invalid body for lambda!".to_string())),
-        RParen => return Err(BadToken("unexpected rparen!".to_string()))
-    };
+    let body = try!(get_body(tokens, env, "lambda"));
+    Ok((symbols, body))
+}
 
-    if try!(strip(tokens.peek())) != RParen {
-        Err(BadToken(format!("{}", try!(strip(tokens.peek())))))
-    } else {
-        tokens.next();
-        Ok((symbols, body))
+/// The bindings of a `let', as in ((x 1) (y (+ x 1))). Each one reads just like
+/// a call, so it is translated as one and then taken apart.
+pub fn bindings(tokens: &mut TokenStream<Token, ErrorKind>,
+                env: &mut Environment) -> CalcResult<(Vec<LiteralType>, Vec<ArgType>)> {
+    try!(begin_expr(tokens));
+
+    let (mut names, mut values) = (Vec::new(), Vec::new());
+    loop {
+        match try!(strip(tokens.peek())) {
+            LParen => { },
+            RParen => {
+                tokens.next();
+                return Ok((names, values))
+            },
+            x => return Err(BadToken(format!("Expected a binding but found {}", x)))
+        }
+
+        match try!(translate(tokens, env)) {
+            SExpr(ref x) if x.args.len() == 1 => match x.expr_type {
+                Function(ref name) => {
+                    names.push(Symbol(name.clone()));
+                    values.push(x.args[0].clone());
+                },
+                BuiltIn(ref op) => {
+                    return Err(BadToken(format!("Can't bind the builtin `{}'", op)))
+                }
+            },
+            _ => return Err(BadToken("A binding is a name and a value, as in (x 1)".to_string()))
+        }
     }
 }
 
+/// `let', `let*' and `letrec' become the names bound, a list of symbols, then
+/// their values and the body. A named `let' has its name in front.
+pub fn let_form(tokens: &mut TokenStream<Token, ErrorKind>, env: &mut Env,
+                op: OperatorType) -> Expr {
+    let mut args = Vec::new();
+    if op == Let {
+        match try!(strip(tokens.peek())) {
+            Variable(x) => {
+                tokens.next();
+                args.push(Atom(Symbol(x)));
+            },
+            _ => { }
+        }
+    }
+
+    let (names, values) = try!(bindings(tokens, env));
+    args.push(Atom(List(names)));
+    args.push_all(values.as_slice());
+    args.push(try!(get_body(tokens, env, op.to_string().as_slice())));
+
+    Ok(SExpr(Expression::new(sexpr::BuiltIn(op), args)))
+}
+
 pub fn expr_accumulator(tokens: &mut TokenStream<Token, ErrorKind>, 
                         env: &mut Env) -> CalcResult<Vec<ArgType>> {
     use sexpr::Function;
@@ -99,14 +166,21 @@ pub fn expr_accumulator(tokens: &mut TokenStream<Token, ErrorKind>,
     }
 }
 
-pub fn define(tokens: &mut TokenStream<Token, ErrorKind>, env: &mut Env) -> CalcResult {
-    let symbols: Vec<LiteralType> = try!(
+/// `(define x ...)' names a value and `(define (f x) ...)' a procedure, so the
+/// name is kept as a symbol in the first case and as a list in the second, even
+/// when the procedure has no parameters.
+pub fn define(tokens: &mut TokenStream<Token, ErrorKind>, env: &mut Env,
+              op: OperatorType) -> CalcResult {
+    let is_proc = try!(strip(tokens.peek())) == LParen;
+    let mut symbols: Vec<LiteralType> = try!(
         get_symbols(tokens)).move_iter().map(|x| Symbol(x)).collect();
 
     if symbols.len() < 1 {
         return Err(BadArgType("Bad number of symbols".to_string()))
     }
 
+    let names = if is_proc { Atom(List(symbols)) } else { Atom(symbols.pop().unwrap()) };
+
     let body = match try!(strip(tokens.next())) {
         LParen => {
             match tokens.rev(1) {
@@ -129,8 +203,8 @@ pub fn define(tokens: &mut TokenStream<Token, ErrorKind>, env: &mut Env) -> Calc
 
     if try!(strip(tokens.peek())) == RParen {
         tokens.next();
-        let expr = SExpr(Expression::new(sexpr::BuiltIn(Define), 
-                                 vec!(Atom(List(symbols))).append(body.as_slice())));
+        let expr = SExpr(Expression::new(sexpr::BuiltIn(op), 
+                                 vec!(names).append(body.as_slice())));
         Ok(expr)
     } else {
         Err(BadToken(format!("{}", try!(strip(tokens.peek())))))
@@ -145,9 +219,15 @@ pub fn handle_operator(tokens: &mut TokenStream<Token, ErrorKind>, env: &mut Env
         },
 
         _   => match op {
-            Quote => {
-                let list = try!(list_it(tokens, env));
-                Ok(Atom(List(list)))
+            Quote => match try!(strip(tokens.peek())) {
+                Variable(x) => {
+                    tokens.next();
+                    Ok(Atom(Symbol(x)))
+                },
+                _ => {
+                    let list = try!(list_it(tokens, env));
+                    Ok(Atom(List(list)))
+                }
             },
             
             _ => return Err(BadToken(format!("Operator in wrong place: {}", op)))
@@ -217,14 +297,22 @@ pub fn make_expr(etype: ExprType, tokens: &mut TokenStream<Token, ErrorKind>,
                  env: &mut Env) -> Expr {
 
     match etype {
-        sexpr::BuiltIn(Define)    => define(tokens, env),
+        sexpr::BuiltIn(Define)    => define(tokens, env, Define),
+        sexpr::BuiltIn(Redefine)  => define(tokens, env, Redefine),
         sexpr::BuiltIn(Lambda)    => {
             let (symbols, body) = try!(lambda(tokens, env));
-            match body {
-                Atom(_) => Ok(body),
-                SExpr(x) => Ok(Atom(Proc(symbols, x))),
-            }
+            let body = match body {
+                SExpr(x) => x,
+                x => Expression::new(sexpr::BuiltIn(Begin), vec![x])
+            };
+            // the procedure is made when the lambda is evaluated, so that it can
+            // close over the environment it is evaluated in
+            let made = Atom(Proc(symbols, body, Global));
+            Ok(SExpr(Expression::new(sexpr::BuiltIn(Lambda), vec![made])))
         }, 
+        sexpr::BuiltIn(Let)       => let_form(tokens, env, Let),
+        sexpr::BuiltIn(LetStar)   => let_form(tokens, env, LetStar),
+        sexpr::BuiltIn(LetRec)    => let_form(tokens, env, LetRec),
         sexpr::BuiltIn(Quote)     => {
             let list = try!(list_it(tokens, env));
             Ok(Atom(List(list)))
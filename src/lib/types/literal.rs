@@ -4,18 +4,24 @@ extern crate num;
 extern crate matrix;
 
 use self::matrix::{Matrice};
-use super::{BigRational, CalcResult, Expression, Environment};
+use super::{BigRational, CalcResult, Expression, Environment, Scope};
+use super::polynomial;
+use super::polynomial::Coeffs;
+use self::num::bigint::BigInt;
 use std::num;
-use std::num::{Zero, One};
+use std::num::{Zero, One, Signed};
 use std::fmt;
 
 #[deriving(Clone, PartialEq, PartialOrd)]
 pub enum LiteralType {
     Boolean(bool),
     BigNum(BigRational),
+    Float(f64),
+    Complex(BigRational, BigRational),
     List(Vec<LiteralType>),
     Matrix(Matrice<LiteralType>),
-    Proc(Vec<String>, Expression),
+    Polynomial(Coeffs),
+    Proc(Vec<String>, Expression, Scope),
     Symbol(String),
     Void
 }
@@ -30,11 +36,14 @@ impl<'a> fmt::Show for WithEnv<'a> {
         match self.data {
             &Boolean(ref x) => try!(write!(fmt, "{}", x)),
             &BigNum(ref x) => try!(write!(fmt, "{}", x)),
+            &Float(x) => try!(write_float(fmt, x)),
+            &Complex(ref re, ref im) => try!(write_complex(fmt, re, im)),
             &List(ref list) => try!(write!(fmt, "{}", list)),
             &Matrix(ref m) => try!(write!(fmt, "{}", m)),
-            &Proc(ref args, ref expr) => {
-                try!(write!(fmt, "Procedure: parameters: {}, body: {}", args, expr))
+            &Polynomial(ref c) => {
+                try!(write!(fmt, "{}", polynomial::format(c, |x| x.to_string())))
             },
+            &Proc(ref args, ref expr, _) => try!(write_proc(fmt, args, expr)),
             &Symbol(ref s) => try!(write!(fmt, "{} {}", s, match self.env.lookup(s) {
                 Ok(x) => format!("= {}", x),
                 Err(m) => m.to_string(),
@@ -51,11 +60,14 @@ impl fmt::Show for LiteralType {
         match *self {
             Boolean(ref x) => try!(write!(fmt, "{}", x)),
             BigNum(ref x) => try!(write!(fmt, "{}", x)),
+            Float(x) => try!(write_float(fmt, x)),
+            Complex(ref re, ref im) => try!(write_complex(fmt, re, im)),
             List(ref list) => try!(write!(fmt, "{}", list)),
             Matrix(ref m) => try!(write!(fmt, "{}", m)),
-            Proc(ref args, ref expr) => {
-                try!(write!(fmt, "Procedure: parameters: {}, body: {}", args, expr))
+            Polynomial(ref c) => {
+                try!(write!(fmt, "{}", polynomial::format(c, |x| x.to_string())))
             },
+            Proc(ref args, ref expr, _) => try!(write_proc(fmt, args, expr)),
             Symbol(ref s) => try!(write!(fmt, "{}", s)),
             Void => ()
         }
@@ -64,9 +76,74 @@ impl fmt::Show for LiteralType {
     }
 }
 
+/// Writes a float so that it always looks inexact, e.g. `2.0' rather than `2'.
+fn write_float(fmt: &mut fmt::Formatter, x: f64) -> fmt::Result {
+    let s = x.to_string();
+    if x.is_finite() && !s.as_slice().contains_char('.') && !s.as_slice().contains_char('e') {
+        write!(fmt, "{}.0", s)
+    } else {
+        write!(fmt, "{}", s)
+    }
+}
+
+/// Writes a procedure as the lambda that makes it, e.g. `(lambda (x) (* x x))'.
+fn write_proc(fmt: &mut fmt::Formatter, args: &Vec<String>, expr: &Expression) -> fmt::Result {
+    write!(fmt, "(lambda ({}) {})", args.connect(" "), expr)
+}
+
+/// The nearest float to a rational. Values too large for a float become
+/// infinite, and those too small become zero.
+pub fn to_float(x: &BigRational) -> f64 {
+    let bits = |n: &BigInt| n.abs().to_biguint().unwrap().bits() as int;
+
+    // scale the quotient to about 60 bits so that it fits in an i64
+    let shift = bits(x.denom()) - bits(x.numer()) + 60;
+    let quotient = if shift >= 0 {
+        (*x.numer() << shift as uint) / *x.denom()
+    } else {
+        (*x.numer() >> (-shift) as uint) / *x.denom()
+    };
+
+    // the scale is applied in two halves, since 2^-shift on its own can
+    // underflow to zero when the result is still in range
+    let half = shift / 2;
+    quotient.to_f64().unwrap() * 2.0f64.powi(-half as i32) * 2.0f64.powi((half - shift) as i32)
+}
+
+/// Writes a complex number the way it is typed in, e.g. `3-4i' or `1/2i'.
+fn write_complex(fmt: &mut fmt::Formatter, re: &BigRational, im: &BigRational) -> fmt::Result {
+    if re.is_zero() {
+        write!(fmt, "{}i", im)
+    } else if *im < num::zero() {
+        write!(fmt, "{}-{}i", re, -im)
+    } else {
+        write!(fmt, "{}+{}i", re, im)
+    }
+}
+
 pub type Lit = LiteralType;
 pub type LitRes =  CalcResult<LiteralType>;
 
+/// Builds a complex number, collapsing it to a real one if the imaginary part
+/// is zero.
+pub fn complex(re: BigRational, im: BigRational) -> Lit {
+    if im.is_zero() {
+        BigNum(re)
+    } else {
+        Complex(re, im)
+    }
+}
+
+/// Builds a polynomial, collapsing it to a number if it is constant.
+pub fn poly(c: Coeffs) -> Lit {
+    let c = polynomial::normalize(c);
+    match c.len() {
+        0 => BigNum(num::zero()),
+        1 => BigNum(c[0].clone()),
+        _ => Polynomial(c)
+    }
+}
+
 impl Num for Lit { }
 
 impl Zero for Lit {
@@ -77,6 +154,7 @@ impl Zero for Lit {
     fn is_zero(&self) -> bool {
         match self {
             &BigNum(ref x) => x.is_zero(),
+            &Float(x) => x == 0.0,
             _ => false
         }
     }
@@ -92,7 +170,10 @@ impl Neg<Lit> for Lit {
     fn neg(&self) -> Lit {
         match self {
             &BigNum(ref x) => BigNum(-x),
+            &Float(x) => Float(-x),
+            &Complex(ref re, ref im) => Complex(-re, -im),
             &Matrix(ref x) => Matrix(-x),
+            &Polynomial(ref c) => Polynomial(polynomial::neg(c)),
             _ => fail!("Can't negate something that isn't a number!".to_string())
         }
     }
@@ -102,9 +183,22 @@ impl Add<Lit, Lit> for Lit {
     fn add(&self, rhs: &LiteralType) -> Lit {
         match (self, rhs) {
             (&BigNum(ref x), &BigNum(ref y)) => BigNum(x + *y),
+            (&Float(x), &Float(y)) => Float(x + y),
+            (&Float(x), &BigNum(ref y)) => Float(x + to_float(y)),
+            (&BigNum(ref x), &Float(y)) => Float(to_float(x) + y),
+            (&Complex(ref a, ref b), &Complex(ref c, ref d)) => complex(*a + *c, *b + *d),
+            (&Complex(ref a, ref b), &BigNum(ref y)) => complex(*a + *y, b.clone()),
+            (&BigNum(ref x), &Complex(ref c, ref d)) => complex(*x + *c, d.clone()),
             (&Matrix(ref x), &Matrix(ref y)) => Matrix(*x + *y),
             (&Matrix(ref x), &BigNum(_)) => Matrix(x.scalar(rhs, |a, b| a + *b)),
             (&BigNum(_), &Matrix(ref x)) => Matrix(x.scalar(self, |a, b| a + *b)),
+            (&Polynomial(ref a), &Polynomial(ref b)) => poly(polynomial::add(a, b)),
+            (&Polynomial(ref a), &BigNum(ref y)) => {
+                poly(polynomial::add(a, &polynomial::constant(y.clone())))
+            },
+            (&BigNum(ref x), &Polynomial(ref b)) => {
+                poly(polynomial::add(&polynomial::constant(x.clone()), b))
+            },
             _ => fail!(format!("Arithmetic not defined for {} {}", self, rhs))
         }
     }
@@ -114,8 +208,21 @@ impl Sub<Lit, Lit> for Lit {
     fn sub(&self, rhs: &Lit) -> Lit {
         match (self, rhs) {
             (&BigNum(ref x), &BigNum(ref y)) => BigNum(x - *y),
+            (&Float(x), &Float(y)) => Float(x - y),
+            (&Float(x), &BigNum(ref y)) => Float(x - to_float(y)),
+            (&BigNum(ref x), &Float(y)) => Float(to_float(x) - y),
+            (&Complex(ref a, ref b), &Complex(ref c, ref d)) => complex(*a - *c, *b - *d),
+            (&Complex(ref a, ref b), &BigNum(ref y)) => complex(*a - *y, b.clone()),
+            (&BigNum(ref x), &Complex(ref c, ref d)) => complex(*x - *c, -d),
             (&Matrix(ref x), &Matrix(ref y)) => Matrix(*x - *y),
             (&Matrix(ref x), &BigNum(_)) => Matrix(x.scalar(rhs, |a, b| a - *b)),
+            (&Polynomial(ref a), &Polynomial(ref b)) => poly(polynomial::sub(a, b)),
+            (&Polynomial(ref a), &BigNum(ref y)) => {
+                poly(polynomial::sub(a, &polynomial::constant(y.clone())))
+            },
+            (&BigNum(ref x), &Polynomial(ref b)) => {
+                poly(polynomial::sub(&polynomial::constant(x.clone()), b))
+            },
             _ => fail!(format!("Arithmetic not defined for {} {}", self, rhs))
         }
     }
@@ -125,9 +232,19 @@ impl Mul<Lit, Lit> for Lit {
     fn mul(&self, rhs: &Lit) -> Lit {
         match (self, rhs) {
             (&BigNum(ref x), &BigNum(ref y)) => BigNum(x * *y),
+            (&Float(x), &Float(y)) => Float(x * y),
+            (&Float(x), &BigNum(ref y)) => Float(x * to_float(y)),
+            (&BigNum(ref x), &Float(y)) => Float(to_float(x) * y),
+            (&Complex(ref a, ref b), &Complex(ref c, ref d)) => 
+                complex(*a * *c - *b * *d, *a * *d + *b * *c),
+            (&Complex(ref a, ref b), &BigNum(ref y)) => complex(*a * *y, *b * *y),
+            (&BigNum(ref x), &Complex(ref c, ref d)) => complex(*x * *c, *x * *d),
             (&Matrix(ref x), &Matrix(ref y)) => Matrix(*x * *y),
             (&Matrix(ref x), &BigNum(_)) => Matrix(x.scalar(rhs, |a, b| a * *b)),
             (&BigNum(_), &Matrix(ref x)) => Matrix(x.scalar(self, |a, b| a * *b)),
+            (&Polynomial(ref a), &Polynomial(ref b)) => poly(polynomial::mul(a, b)),
+            (&Polynomial(ref a), &BigNum(ref y)) => poly(polynomial::scale(a, y)),
+            (&BigNum(ref x), &Polynomial(ref b)) => poly(polynomial::scale(b, x)),
             _ => fail!(format!("Arithmetic not defined for {} {}", self, rhs))
         }
 
@@ -138,8 +255,25 @@ impl Div<Lit, Lit> for Lit {
     fn div(&self, rhs: &Lit) -> Lit {
         match (self, rhs) {
             (&BigNum(ref x), &BigNum(ref y)) => BigNum(x / *y),
+            (&Float(x), &Float(y)) => Float(x / y),
+            (&Float(x), &BigNum(ref y)) => Float(x / to_float(y)),
+            (&BigNum(ref x), &Float(y)) => Float(to_float(x) / y),
+            (&Complex(ref a, ref b), &Complex(ref c, ref d)) => {
+                let denom = *c * *c + *d * *d;
+                complex((*a * *c + *b * *d) / denom, (*b * *c - *a * *d) / denom)
+            },
+            (&Complex(ref a, ref b), &BigNum(ref y)) => complex(*a / *y, *b / *y),
+            (&BigNum(ref x), &Complex(ref c, ref d)) => {
+                let denom = *c * *c + *d * *d;
+                complex(*x * *c / denom, -(*x * *d) / denom)
+            },
             (&Matrix(ref x), &Matrix(ref y)) => Matrix(*x / *y),
             (&Matrix(ref x), &BigNum(_)) => Matrix(x.scalar(rhs, |a, b| a / *b)),
+            (&Polynomial(ref a), &Polynomial(ref b)) => {
+                let (q, _) = polynomial::div_rem(a, b);
+                poly(q)
+            },
+            (&Polynomial(ref a), &BigNum(ref y)) => poly(polynomial::scale(a, &y.recip())),
              _ => fail!("Division is only defined for numbers".to_string())
         }
     }
@@ -149,7 +283,18 @@ impl Rem<Lit, Lit> for Lit {
     fn rem(&self, rhs: &Lit) -> Lit {
         match (self, rhs) {
             (&BigNum(ref x), &BigNum(ref y)) => BigNum(x % *y),
+            (&Float(x), &Float(y)) => Float(x % y),
+            (&Float(x), &BigNum(ref y)) => Float(x % to_float(y)),
+            (&BigNum(ref x), &Float(y)) => Float(to_float(x) % y),
             (&Matrix(ref x), &BigNum(_)) => Matrix(x.scalar(rhs, |a, b| a % *b)),
+            (&Polynomial(ref a), &Polynomial(ref b)) => {
+                let (_, r) = polynomial::div_rem(a, b);
+                poly(r)
+            },
+            // a constant divides any polynomial, and leaves a constant behind
+            // when divided by a polynomial of higher degree
+            (&Polynomial(_), &BigNum(_)) => BigNum(num::zero()),
+            (&BigNum(ref x), &Polynomial(_)) => BigNum(x.clone()),
             _ => fail!("Rem is only defined for numbers".to_string())
         }
     }
@@ -68,7 +68,7 @@ impl fmt::Show for Transcendental {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         try!(write!(fmt, "{}", match self {
             &Log => "log", &Ln => "ln", &Exp => "exp",
-            &Sin => "sin", &Cos => "cos", &Tan => "cos",
+            &Sin => "sin", &Cos => "cos", &Tan => "tan",
             &ASin => "asin", &ACos => "acos", &ATan => "atan",
             &SinH => "sinh", &CosH => "cosh", &TanH => "tanh",
             &ASinH => "asinh", &ACosH => "acosh", &ATanH => "atanh"
@@ -300,6 +300,7 @@ pub enum MatrixOps {
     MatrixGetCol,
     Determ,
     MatrixInv,
+    MatrixCharPoly,
     MatrixFromFn,
 }
 
@@ -316,6 +317,7 @@ impl fmt::Show for MatrixOps {
             &MatrixGetCol => "matrix-get-col",
             &Determ => "matrix-det",
             &MatrixInv => "matrix-inv",
+            &MatrixCharPoly => "matrix-char-poly",
             &MatrixFromFn => "matrix-from-fn",
         }));
         Ok(())
@@ -335,12 +337,532 @@ impl from_str::FromStr for MatrixOps {
             "matrix-get-col" => Some(MatrixGetCol),
             "matrix-det" => Some(Determ),
             "matrix-inv" => Some(MatrixInv),
+            "matrix-char-poly" => Some(MatrixCharPoly),
             "matrix-from-fn" => Some(MatrixFromFn),
             _ => None
         }
     }
 }
 
+#[deriving(Clone, PartialOrd, PartialEq)]
+pub enum ComplexOp {
+    RealPart,
+    ImagPart,
+    Conj,
+    Abs,
+    Arg,
+}
+
+impl fmt::Show for ComplexOp {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{}", match self {
+            &RealPart => "re",
+            &ImagPart => "im",
+            &Conj => "conj",
+            &Abs => "abs",
+            &Arg => "arg",
+        }));
+        Ok(())
+    }
+}
+
+impl from_str::FromStr for ComplexOp {
+    fn from_str(s: &str) -> Option<ComplexOp> {
+        match s {
+            "re" => Some(RealPart),
+            "im" => Some(ImagPart),
+            "conj" => Some(Conj),
+            "abs" => Some(Abs),
+            "arg" => Some(Arg),
+            _ => None
+        }
+    }
+}
+
+#[deriving(Clone, PartialOrd, PartialEq)]
+pub enum ExactOp {
+    ToInexact,
+    ToExact,
+    IsExact,
+    IsInexact,
+}
+
+impl fmt::Show for ExactOp {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{}", match self {
+            &ToInexact => "exact->inexact",
+            &ToExact => "inexact->exact",
+            &IsExact => "exact?",
+            &IsInexact => "inexact?",
+        }));
+        Ok(())
+    }
+}
+
+impl from_str::FromStr for ExactOp {
+    fn from_str(s: &str) -> Option<ExactOp> {
+        match s {
+            "exact->inexact" => Some(ToInexact),
+            "inexact->exact" => Some(ToExact),
+            "exact?" => Some(IsExact),
+            "inexact?" => Some(IsInexact),
+            _ => None
+        }
+    }
+}
+
+#[deriving(Clone, PartialOrd, PartialEq)]
+pub enum NumTheory {
+    Gcd,
+    Lcm,
+    ModPow,
+    ModInv,
+    Crt,
+    Totient,
+    Divisors,
+    Sigma,
+    Jacobi,
+}
+
+impl fmt::Show for NumTheory {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{}", match self {
+            &Gcd => "gcd",
+            &Lcm => "lcm",
+            &ModPow => "mod-pow",
+            &ModInv => "mod-inv",
+            &Crt => "crt",
+            &Totient => "totient",
+            &Divisors => "divisors",
+            &Sigma => "sigma",
+            &Jacobi => "jacobi",
+        }));
+        Ok(())
+    }
+}
+
+impl from_str::FromStr for NumTheory {
+    fn from_str(s: &str) -> Option<NumTheory> {
+        match s {
+            "gcd" => Some(Gcd),
+            "lcm" => Some(Lcm),
+            "mod-pow" => Some(ModPow),
+            "mod-inv" => Some(ModInv),
+            "crt" => Some(Crt),
+            "totient" => Some(Totient),
+            "divisors" => Some(Divisors),
+            "sigma" => Some(Sigma),
+            "jacobi" => Some(Jacobi),
+            _ => None
+        }
+    }
+}
+
+#[deriving(Clone, PartialOrd, PartialEq)]
+pub enum PrimeOp {
+    IsPrime,
+    NextPrime,
+    PrevPrime,
+    PrimesUpTo,
+    Factor,
+}
+
+impl fmt::Show for PrimeOp {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{}", match self {
+            &IsPrime => "prime?",
+            &NextPrime => "next-prime",
+            &PrevPrime => "prev-prime",
+            &PrimesUpTo => "primes-up-to",
+            &Factor => "factor",
+        }));
+        Ok(())
+    }
+}
+
+impl from_str::FromStr for PrimeOp {
+    fn from_str(s: &str) -> Option<PrimeOp> {
+        match s {
+            "prime?" => Some(IsPrime),
+            "next-prime" => Some(NextPrime),
+            "prev-prime" => Some(PrevPrime),
+            "primes-up-to" => Some(PrimesUpTo),
+            "factor" => Some(Factor),
+            _ => None
+        }
+    }
+}
+
+#[deriving(Clone, PartialOrd, PartialEq)]
+pub enum Combinatoric {
+    Factorial,
+    Choose,
+    Permutations,
+    Multinomial,
+    Stirling1,
+    Stirling2,
+    Bell,
+    Catalan,
+    Partitions,
+    Fib,
+}
+
+impl fmt::Show for Combinatoric {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{}", match self {
+            &Factorial => "factorial",
+            &Choose => "choose",
+            &Permutations => "permutations",
+            &Multinomial => "multinomial",
+            &Stirling1 => "stirling1",
+            &Stirling2 => "stirling2",
+            &Bell => "bell",
+            &Catalan => "catalan",
+            &Partitions => "partitions",
+            &Fib => "fib",
+        }));
+        Ok(())
+    }
+}
+
+impl from_str::FromStr for Combinatoric {
+    fn from_str(s: &str) -> Option<Combinatoric> {
+        match s {
+            "factorial" => Some(Factorial),
+            "choose" => Some(Choose),
+            "permutations" => Some(Permutations),
+            "multinomial" => Some(Multinomial),
+            "stirling1" => Some(Stirling1),
+            "stirling2" => Some(Stirling2),
+            "bell" => Some(Bell),
+            "catalan" => Some(Catalan),
+            "partitions" => Some(Partitions),
+            "fib" => Some(Fib),
+            _ => None
+        }
+    }
+}
+
+#[deriving(Clone, PartialOrd, PartialEq)]
+pub enum StatOp {
+    Mean,
+    Median,
+    Mode,
+    Variance,
+    PVariance,
+    StDev,
+    PStDev,
+    Quantile,
+    Min,
+    Max,
+    Sum,
+    Product,
+    Covariance,
+    Correlation,
+    ZScore,
+    WeightedMean,
+}
+
+impl fmt::Show for StatOp {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{}", match self {
+            &Mean => "mean",
+            &Median => "median",
+            &Mode => "mode",
+            &Variance => "variance",
+            &PVariance => "pvariance",
+            &StDev => "stdev",
+            &PStDev => "pstdev",
+            &Quantile => "quantile",
+            &Min => "min",
+            &Max => "max",
+            &Sum => "sum",
+            &Product => "product",
+            &Covariance => "covariance",
+            &Correlation => "correlation",
+            &ZScore => "z-score",
+            &WeightedMean => "weighted-mean",
+        }));
+        Ok(())
+    }
+}
+
+impl from_str::FromStr for StatOp {
+    fn from_str(s: &str) -> Option<StatOp> {
+        match s {
+            "mean" => Some(Mean),
+            "median" => Some(Median),
+            "mode" => Some(Mode),
+            "variance" => Some(Variance),
+            "pvariance" => Some(PVariance),
+            "stdev" => Some(StDev),
+            "pstdev" => Some(PStDev),
+            "quantile" => Some(Quantile),
+            "min" => Some(Min),
+            "max" => Some(Max),
+            "sum" => Some(Sum),
+            "product" | "prod" => Some(Product),
+            "covariance" => Some(Covariance),
+            "correlation" => Some(Correlation),
+            "z-score" => Some(ZScore),
+            "weighted-mean" => Some(WeightedMean),
+            _ => None
+        }
+    }
+}
+
+#[deriving(Clone, PartialOrd, PartialEq)]
+pub enum FitOp {
+    LinReg,
+    PolyFit,
+    LinRegFn,
+    PolyFitFn,
+}
+
+impl fmt::Show for FitOp {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{}", match self {
+            &LinReg => "linreg",
+            &PolyFit => "polyfit",
+            &LinRegFn => "linreg-fn",
+            &PolyFitFn => "polyfit-fn",
+        }));
+        Ok(())
+    }
+}
+
+impl from_str::FromStr for FitOp {
+    fn from_str(s: &str) -> Option<FitOp> {
+        match s {
+            "linreg" => Some(LinReg),
+            "polyfit" => Some(PolyFit),
+            "linreg-fn" => Some(LinRegFn),
+            "polyfit-fn" => Some(PolyFitFn),
+            _ => None
+        }
+    }
+}
+
+#[deriving(Clone, PartialOrd, PartialEq)]
+pub enum Distribution {
+    Normal,
+    Binomial,
+    Poisson,
+    Uniform,
+    Exponential,
+    StudentT,
+    ChiSquared,
+    FisherF,
+}
+
+impl Distribution {
+    /// Discrete distributions have a pmf where the others have a pdf.
+    pub fn is_discrete(&self) -> bool {
+        match *self {
+            Binomial | Poisson => true,
+            _ => false
+        }
+    }
+}
+
+impl fmt::Show for Distribution {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{}", match self {
+            &Normal => "normal",
+            &Binomial => "binomial",
+            &Poisson => "poisson",
+            &Uniform => "uniform",
+            &Exponential => "exponential",
+            &StudentT => "student-t",
+            &ChiSquared => "chi-squared",
+            &FisherF => "f",
+        }));
+        Ok(())
+    }
+}
+
+impl from_str::FromStr for Distribution {
+    fn from_str(s: &str) -> Option<Distribution> {
+        match s {
+            "normal" => Some(Normal),
+            "binomial" => Some(Binomial),
+            "poisson" => Some(Poisson),
+            "uniform" => Some(Uniform),
+            "exponential" => Some(Exponential),
+            "student-t" => Some(StudentT),
+            "chi-squared" => Some(ChiSquared),
+            "f" => Some(FisherF),
+            _ => None
+        }
+    }
+}
+
+#[deriving(Clone, PartialOrd, PartialEq)]
+pub enum DistFn {
+    Density,
+    Cumulative,
+    InverseCumulative,
+    Draw,
+}
+
+impl fmt::Show for DistFn {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{}", match self {
+            &Density => "pdf",
+            &Cumulative => "cdf",
+            &InverseCumulative => "quantile",
+            &Draw => "sample",
+        }));
+        Ok(())
+    }
+}
+
+#[deriving(Clone, PartialOrd, PartialEq)]
+pub enum RandomOp {
+    Random,
+    RandomInt,
+    RandomRational,
+    Shuffle,
+    Sample,
+    Seed,
+}
+
+impl fmt::Show for RandomOp {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{}", match self {
+            &Random => "random",
+            &RandomInt => "random-int",
+            &RandomRational => "random-rational",
+            &Shuffle => "shuffle",
+            &Sample => "sample",
+            &Seed => "seed",
+        }));
+        Ok(())
+    }
+}
+
+impl from_str::FromStr for RandomOp {
+    fn from_str(s: &str) -> Option<RandomOp> {
+        match s {
+            "random" => Some(Random),
+            "random-int" => Some(RandomInt),
+            "random-rational" => Some(RandomRational),
+            "shuffle" => Some(Shuffle),
+            "sample" => Some(Sample),
+            "seed" => Some(Seed),
+            _ => None
+        }
+    }
+}
+
+#[deriving(Clone, PartialOrd, PartialEq)]
+pub enum CalculusOp {
+    Integrate,
+    IntegrateWithError,
+    Derivative,
+    Limit,
+}
+
+impl fmt::Show for CalculusOp {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{}", match self {
+            &Integrate => "integrate",
+            &IntegrateWithError => "integrate-with-error",
+            &Derivative => "derivative",
+            &Limit => "limit",
+        }));
+        Ok(())
+    }
+}
+
+impl from_str::FromStr for CalculusOp {
+    fn from_str(s: &str) -> Option<CalculusOp> {
+        match s {
+            "integrate" => Some(Integrate),
+            "integrate-with-error" => Some(IntegrateWithError),
+            "derivative" => Some(Derivative),
+            "limit" => Some(Limit),
+            _ => None
+        }
+    }
+}
+
+#[deriving(Clone, PartialOrd, PartialEq)]
+pub enum SolveOp {
+    FindRoot,
+    Newton,
+    Minimize,
+}
+
+impl fmt::Show for SolveOp {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{}", match self {
+            &FindRoot => "find-root",
+            &Newton => "newton",
+            &Minimize => "minimize",
+        }));
+        Ok(())
+    }
+}
+
+impl from_str::FromStr for SolveOp {
+    fn from_str(s: &str) -> Option<SolveOp> {
+        match s {
+            "find-root" => Some(FindRoot),
+            "newton" => Some(Newton),
+            "minimize" => Some(Minimize),
+            _ => None
+        }
+    }
+}
+
+#[deriving(Clone, PartialOrd, PartialEq)]
+pub enum PolyOp {
+    MakePoly,
+    PolyCoeffs,
+    PolyEval,
+    PolyDivRem,
+    PolyGcd,
+    PolyDeriv,
+    PolyIntegral,
+    PolyDegree,
+    PolyRoots,
+}
+
+impl fmt::Show for PolyOp {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{}", match self {
+            &MakePoly => "poly",
+            &PolyCoeffs => "poly-coeffs",
+            &PolyEval => "poly-eval",
+            &PolyDivRem => "poly-div",
+            &PolyGcd => "poly-gcd",
+            &PolyDeriv => "poly-deriv",
+            &PolyIntegral => "poly-integral",
+            &PolyDegree => "poly-degree",
+            &PolyRoots => "poly-roots",
+        }));
+        Ok(())
+    }
+}
+
+impl from_str::FromStr for PolyOp {
+    fn from_str(s: &str) -> Option<PolyOp> {
+        match s {
+            "poly" => Some(MakePoly),
+            "poly-coeffs" => Some(PolyCoeffs),
+            "poly-eval" => Some(PolyEval),
+            "poly-div" => Some(PolyDivRem),
+            "poly-gcd" => Some(PolyGcd),
+            "poly-deriv" => Some(PolyDeriv),
+            "poly-integral" => Some(PolyIntegral),
+            "poly-degree" => Some(PolyDegree),
+            "poly-roots" => Some(PolyRoots),
+            _ => None
+        }
+    }
+}
+
 #[deriving(Clone, PartialOrd, PartialEq)]
 pub enum OperatorType {
     Arithmetic(Arith),
@@ -353,10 +875,33 @@ pub enum OperatorType {
     Listings(ListOps),
     TransForms(XForms),
     Define,
+    Redefine,
     Lambda,
+    Let,
+    LetStar,
+    LetRec,
+    Begin,
+    SetVar,
     Table, 
     TableFromMatrix,
     MatrixStuff(MatrixOps),
+    ComplexOps(ComplexOp),
+    Exactness(ExactOp),
+    NumberTheory(NumTheory),
+    PrimeOps(PrimeOp),
+    Combinatorics(Combinatoric),
+    Statistics(StatOp),
+    Regression(FitOp),
+    Probability(Distribution, DistFn),
+    Randomness(RandomOp),
+    Calculus(CalculusOp),
+    Solver(SolveOp),
+    Series,
+    Differentiate,
+    Polynomials(PolyOp),
+    Precision,
+    Display,
+    ToBase,
     Help,
 }
 
@@ -371,12 +916,36 @@ impl fmt::Show for OperatorType {
             Listings(ref x) => x.to_string(),
             TransForms(ref x) => x.to_string(),
             MatrixStuff(ref x) => x.to_string(),
+            ComplexOps(ref x) => x.to_string(),
+            Exactness(ref x) => x.to_string(),
+            NumberTheory(ref x) => x.to_string(),
+            PrimeOps(ref x) => x.to_string(),
+            Combinatorics(ref x) => x.to_string(),
+            Statistics(ref x) => x.to_string(),
+            Regression(ref x) => x.to_string(),
+            Probability(ref d, Density) if d.is_discrete() => format!("{}-pmf", d),
+            Probability(ref d, ref f) => format!("{}-{}", d, f),
+            Randomness(ref x) => x.to_string(),
+            Calculus(ref x) => x.to_string(),
+            Solver(ref x) => x.to_string(),
+            Polynomials(ref x) => x.to_string(),
             Pow => "pow".to_string(),
             Quote => "'".to_string(),
             Define => "define".to_string(),
+            Redefine => "redefine".to_string(),
             Lambda => "lambda".to_string(),
+            Let => "let".to_string(),
+            LetStar => "let*".to_string(),
+            LetRec => "letrec".to_string(),
+            Begin => "begin".to_string(),
+            SetVar => "set!".to_string(),
             Table => "table".to_string(),
             TableFromMatrix => "table-from-matrix".to_string(),
+            Precision => "set-precision".to_string(),
+            Display => "set-display".to_string(),
+            ToBase => "to-base".to_string(),
+            Series => "series".to_string(),
+            Differentiate => "d/dx".to_string(),
             Help => "help".to_string(),
         }));
         Ok(())
@@ -419,14 +988,93 @@ impl from_str::FromStr for OperatorType {
             Some(x) => return Some(MatrixStuff(x)),
             None => { }
         }
+
+        match from_str::<ComplexOp>(s) {
+            Some(x) => return Some(ComplexOps(x)),
+            None => { }
+        }
+
+        match from_str::<ExactOp>(s) {
+            Some(x) => return Some(Exactness(x)),
+            None => { }
+        }
+
+        match from_str::<NumTheory>(s) {
+            Some(x) => return Some(NumberTheory(x)),
+            None => { }
+        }
+
+        match from_str::<PrimeOp>(s) {
+            Some(x) => return Some(PrimeOps(x)),
+            None => { }
+        }
+
+        match from_str::<Combinatoric>(s) {
+            Some(x) => return Some(Combinatorics(x)),
+            None => { }
+        }
+
+        match from_str::<StatOp>(s) {
+            Some(x) => return Some(Statistics(x)),
+            None => { }
+        }
+
+        match from_str::<FitOp>(s) {
+            Some(x) => return Some(Regression(x)),
+            None => { }
+        }
+
+        match from_str::<RandomOp>(s) {
+            Some(x) => return Some(Randomness(x)),
+            None => { }
+        }
+
+        match from_str::<CalculusOp>(s) {
+            Some(x) => return Some(Calculus(x)),
+            None => { }
+        }
+
+        match from_str::<SolveOp>(s) {
+            Some(x) => return Some(Solver(x)),
+            None => { }
+        }
+
+        match from_str::<PolyOp>(s) {
+            Some(x) => return Some(Polynomials(x)),
+            None => { }
+        }
+
+        // distributions are named like `normal-cdf' and `binomial-pmf'
+        match s.rfind('-') {
+            Some(i) => match (from_str::<Distribution>(s.slice_to(i)), s.slice_from(i + 1)) {
+                (Some(d), "pmf") if d.is_discrete() => return Some(Probability(d, Density)),
+                (Some(d), "pdf") if !d.is_discrete() => return Some(Probability(d, Density)),
+                (Some(d), "cdf") => return Some(Probability(d, Cumulative)),
+                (Some(d), "quantile") => return Some(Probability(d, InverseCumulative)),
+                (Some(d), "sample") => return Some(Probability(d, Draw)),
+                _ => { }
+            },
+            None => { }
+        }
     
         match s {
             "pow" => Some(Pow),
             "define" => Some(Define),
+            "redefine" => Some(Redefine),
             "lambda" => Some(Lambda),
+            "let" => Some(Let),
+            "let*" => Some(LetStar),
+            "letrec" => Some(LetRec),
+            "begin" => Some(Begin),
+            "set!" => Some(SetVar),
             "quote" | "'" => Some(Quote),
             "table" => Some(Table),
             "table-from-matrix" => Some(TableFromMatrix),
+            "set-precision" => Some(Precision),
+            "set-display" => Some(Display),
+            "to-base" => Some(ToBase),
+            "series" => Some(Series),
+            "d/dx" => Some(Differentiate),
             "help" => Some(Help),
             _ => None
         }
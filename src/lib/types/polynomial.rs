@@ -0,0 +1,173 @@
+//! Polynomials in one variable with rational coefficients. A polynomial is
+//! held as its coefficients, constant term first, with no trailing zeros, so
+//! the zero polynomial has no coefficients at all.
+
+use std::num;
+use std::num::{Zero, One, Signed};
+use std::cmp::max;
+use super::{BigRational, Ratio};
+use super::bigint::BigInt;
+
+pub type Coeffs = Vec<BigRational>;
+
+fn int(n: uint) -> BigRational {
+    Ratio::from_integer(num::from_uint::<BigInt>(n).unwrap())
+}
+
+fn coeff(a: &Coeffs, i: uint) -> BigRational {
+    if i < a.len() { a[i].clone() } else { Zero::zero() }
+}
+
+/// Drops the zero coefficients of the highest powers.
+pub fn normalize(mut a: Coeffs) -> Coeffs {
+    while a.last().map_or(false, |x| x.is_zero()) {
+        a.pop();
+    }
+    a
+}
+
+/// The degree, taking the zero polynomial to be of degree 0.
+pub fn degree(a: &Coeffs) -> uint {
+    if a.len() == 0 { 0 } else { a.len() - 1 }
+}
+
+pub fn constant(x: BigRational) -> Coeffs {
+    normalize(vec![x])
+}
+
+pub fn add(a: &Coeffs, b: &Coeffs) -> Coeffs {
+    normalize(range(0, max(a.len(), b.len())).map(|i| coeff(a, i) + coeff(b, i)).collect())
+}
+
+pub fn neg(a: &Coeffs) -> Coeffs {
+    a.iter().map(|x| -*x).collect()
+}
+
+pub fn sub(a: &Coeffs, b: &Coeffs) -> Coeffs {
+    add(a, &neg(b))
+}
+
+pub fn scale(a: &Coeffs, r: &BigRational) -> Coeffs {
+    normalize(a.iter().map(|x| *x * *r).collect())
+}
+
+pub fn mul(a: &Coeffs, b: &Coeffs) -> Coeffs {
+    if a.len() == 0 || b.len() == 0 {
+        return Vec::new()
+    }
+
+    let mut c: Coeffs = Vec::from_elem(a.len() + b.len() - 1, Zero::zero());
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            let sum = c[i + j] + *x * *y;
+            *c.get_mut(i + j) = sum;
+        }
+    }
+
+    normalize(c)
+}
+
+/// Long division, giving q and r with a = qb + r and r of lower degree than b,
+/// which must not be zero.
+pub fn div_rem(a: &Coeffs, b: &Coeffs) -> (Coeffs, Coeffs) {
+    let mut r = a.clone();
+    if a.len() < b.len() {
+        return (Vec::new(), r)
+    }
+
+    let lead = b[b.len() - 1].clone();
+    let mut q: Coeffs = Vec::from_elem(a.len() - b.len() + 1, Zero::zero());
+    for k in range(0, q.len()).rev() {
+        let factor = r[k + b.len() - 1] / lead;
+        for (j, y) in b.iter().enumerate() {
+            let next = r[k + j] - factor * *y;
+            *r.get_mut(k + j) = next;
+        }
+        *q.get_mut(k) = factor;
+    }
+
+    (normalize(q), normalize(r))
+}
+
+/// The value at x, by Horner's rule.
+pub fn eval(a: &Coeffs, x: &BigRational) -> BigRational {
+    a.iter().rev().fold(Zero::zero(), |acc: BigRational, c| acc * *x + *c)
+}
+
+pub fn derivative(a: &Coeffs) -> Coeffs {
+    a.iter().enumerate().skip(1).map(|(i, x)| *x * int(i)).collect()
+}
+
+/// The integral with a constant term of zero.
+pub fn integral(a: &Coeffs) -> Coeffs {
+    if a.len() == 0 {
+        return Vec::new()
+    }
+
+    let mut c: Coeffs = vec![Zero::zero()];
+    c.extend(a.iter().enumerate().map(|(i, x)| *x / int(i + 1)));
+    c
+}
+
+/// Scales a polynomial so that its leading coefficient is 1.
+pub fn monic(a: &Coeffs) -> Coeffs {
+    match a.last() {
+        Some(lead) => scale(a, &lead.recip()),
+        None => Vec::new()
+    }
+}
+
+/// The monic greatest common divisor, by Euclid's algorithm. The gcd of two
+/// zero polynomials is zero.
+pub fn gcd(a: &Coeffs, b: &Coeffs) -> Coeffs {
+    let (mut a, mut b) = (a.clone(), b.clone());
+    while b.len() > 0 {
+        let (_, r) = div_rem(&a, &b);
+        a = b;
+        b = r;
+    }
+    monic(&a)
+}
+
+/// Writes a polynomial the conventional way, highest power first, as in
+/// `3x^2 + 2x - 1'. `number' writes the size of each coefficient, and those
+/// that aren't whole numbers are put in parentheses, as in `(1/2)x'.
+pub fn format(a: &Coeffs, number: |&BigRational| -> String) -> String {
+    if a.len() == 0 {
+        return "0".to_string()
+    }
+
+    let one: BigRational = One::one();
+    let mut s = String::new();
+    for (i, x) in a.iter().enumerate().rev() {
+        if x.is_zero() {
+            continue
+        }
+
+        if s.len() == 0 {
+            if x.is_negative() {
+                s.push_str("-");
+            }
+        } else {
+            s.push_str(if x.is_negative() { " - " } else { " + " });
+        }
+
+        let size = x.abs();
+        if i == 0 || size != one {
+            let digits = number(&size);
+            if i > 0 && !size.is_integer() {
+                s.push_str(format!("({})", digits).as_slice());
+            } else {
+                s.push_str(digits.as_slice());
+            }
+        }
+
+        match i {
+            0 => { },
+            1 => s.push_str("x"),
+            _ => s.push_str(format!("x^{}", i).as_slice())
+        }
+    }
+
+    s
+}
@@ -1,6 +1,9 @@
 //! Expressions
 
+use std::fmt;
 use super::{LiteralType, Environment, OperatorType};
+use super::literal::{Proc, List, Symbol};
+use super::operator::{Lambda, Let, LetStar, LetRec};
 
 #[deriving(Show, Clone, PartialEq, PartialOrd)]
 pub enum ExprType {
@@ -8,7 +11,7 @@ pub enum ExprType {
     Function(String)
 }
 
-#[deriving(Clone, Show, PartialEq, PartialOrd)]
+#[deriving(Clone, PartialEq, PartialOrd)]
 pub struct Expression {
     pub expr_type: ExprType,
     pub args: Vec<ArgType>,
@@ -18,29 +21,77 @@ impl Expression {
     pub fn new(e: ExprType, a: Vec<ArgType>) -> Expression {
         Expression { expr_type: e, args: a }
     }
-    pub fn to_symbol(&self, env: &mut Environment) -> String {
-        let mut symbols = String::new();
-        symbols = symbols.append("(");
+    pub fn to_symbol(&self, _: &mut Environment) -> String {
+        self.to_string()
+    }
+}
+
+/// Writes an expression back out the way it would be typed in.
+impl fmt::Show for Expression {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self.expr_type {
-            Function(ref f) => {
-                symbols = symbols.append(f.as_slice());
-            }
-            BuiltIn(ref op) => {
-                symbols = symbols.append(op.to_string().append(" ").as_slice());
-            }
+            // a lambda holds the procedure it makes, which writes itself out
+            BuiltIn(Lambda) if self.args.len() == 1 => match self.args[0] {
+                Atom(ref x @ Proc(..)) => return write!(fmt, "{}", x),
+                _ => { }
+            },
+            // the names and values of a let are written back in pairs
+            BuiltIn(op @ Let) | BuiltIn(op @ LetStar) | BuiltIn(op @ LetRec) => {
+                let (name, start) = match self.args.as_slice().head() {
+                    Some(&Atom(Symbol(ref x))) => (Some(x), 1u),
+                    _ => (None, 0u)
+                };
+                match self.args.as_slice().get(start) {
+                    Some(&Atom(List(ref vars))) if self.args.len() == start + vars.len() + 2 => {
+                        return write_let(fmt, op, name, vars, self.args.slice_from(start + 1))
+                    },
+                    _ => { }
+                }
+            },
+            _ => { }
         }
+
+        match self.expr_type {
+            BuiltIn(ref op) => try!(write!(fmt, "({}", op)),
+            Function(ref f) => try!(write!(fmt, "({}", f)),
+        }
+
         for argument in self.args.iter() {
-            let arg = match argument {
-                &Atom(ref x) => x.to_string(),
-                &SExpr(ref x) => x.to_symbol(env),
-            };
-            symbols = symbols.append(arg.append(" ").as_slice());
+            try!(write!(fmt, " "));
+            try!(write_arg(fmt, argument));
         }
-        symbols = symbols.append(")");
-        symbols
+        write!(fmt, ")")
     }
 }
 
+fn write_arg(fmt: &mut fmt::Formatter, arg: &ArgType) -> fmt::Result {
+    match *arg {
+        Atom(ref x) => write!(fmt, "{}", x),
+        SExpr(ref x) => write!(fmt, "{}", x),
+    }
+}
+
+/// Writes `(let name ((x 1) (y 2)) body)', given the values and then the body.
+fn write_let(fmt: &mut fmt::Formatter, op: OperatorType, name: Option<&String>,
+             vars: &Vec<LiteralType>, rest: &[ArgType]) -> fmt::Result {
+    try!(write!(fmt, "({} ", op));
+    match name {
+        Some(x) => try!(write!(fmt, "{} ", x)),
+        None => { }
+    }
+
+    try!(write!(fmt, "("));
+    for (i, (var, val)) in vars.iter().zip(rest.iter()).enumerate() {
+        try!(write!(fmt, "{}({} ", if i > 0 { " " } else { "" }, var));
+        try!(write_arg(fmt, val));
+        try!(write!(fmt, ")"));
+    }
+    try!(write!(fmt, ") "));
+
+    try!(write_arg(fmt, &rest[rest.len() - 1]));
+    write!(fmt, ")")
+}
+
 #[deriving(Clone, Show, PartialEq, PartialOrd)]
 pub enum ArgType {
     Atom(LiteralType),
@@ -11,12 +11,18 @@ pub use self::num::bigint;
 pub use literal::{LiteralType};
 pub use sexpr::{ArgType, Atom, SExpr, Expression};
 pub use operator::OperatorType;
-use std::collections::hashmap::HashMap;
+use literal::Proc;
+use std::collections::hashmap::{HashMap, HashSet};
+use std::cmp::{Ordering, Equal};
 use std::fmt;
+use std::rand;
+use std::rand::XorShiftRng;
+use std::sync::{Arc, Mutex, Weak};
 
 pub mod sexpr;
 pub mod literal;
 pub mod operator;
+pub mod polynomial;
 
 #[deriving(Clone, PartialEq)]
 pub enum ErrorKind {
@@ -30,6 +36,7 @@ pub enum ErrorKind {
     DivByZero,
     NonBoolean,
     UnboundArg(String),
+    NoConvergence(String, uint),
 }
 
 impl ErrorKind {
@@ -49,6 +56,7 @@ impl ErrorKind {
             DivByZero => "Attempted division by zero!".to_string(),
             NonBoolean => "Non boolean condition".to_string(),
             UnboundArg(x) => format!("Error: Unbound variable `{}'", x),
+            NoConvergence(x, n) => format!("`{}' did not converge after {} iterations", x, n),
         }
     }
 }
@@ -70,6 +78,9 @@ impl fmt::Show for ErrorKind {
             &DivByZero => "Attempted division by zero!".to_string(),
             &NonBoolean => "Non boolean condition".to_string(),
             &UnboundArg(ref x) => format!("Error: Unbound variable `{}'", x),
+            &NoConvergence(ref x, n) => {
+                format!("`{}' did not converge after {} iterations", x, n)
+            },
         };
         try!(write!(fmt, "{}", res));
         Ok(())
@@ -78,39 +89,283 @@ impl fmt::Show for ErrorKind {
 
 pub type CalcResult<T = ArgType> = Result<T, ErrorKind>;
 
+/// The number of decimal digits transcendental functions are evaluated to
+/// unless the user asks for something else with `set-precision'.
+pub static DEFAULT_PRECISION: uint = 30;
+
+/// The number of digits shown after the point by the decimal, scientific and
+/// engineering display modes when none are given.
+pub static DEFAULT_DISPLAY_DIGITS: uint = 10;
+
+/// How results are written out. The digits are the number of places shown after
+/// the decimal point, and integers can be shown in any base from 2 to 36.
+#[deriving(Clone, PartialEq)]
+pub enum DisplayMode {
+    Fractions,
+    Mixed,
+    Decimal(uint),
+    Scientific(uint),
+    Engineering(uint),
+    Radix(uint),
+    Auto,
+}
+
+impl DisplayMode {
+    pub fn from_name(name: &str, digits: uint) -> Option<DisplayMode> {
+        match name {
+            "fraction" => Some(Fractions),
+            "mixed" => Some(Mixed),
+            "decimal" => Some(Decimal(digits)),
+            "scientific" => Some(Scientific(digits)),
+            "engineering" => Some(Engineering(digits)),
+            "hex" => Some(Radix(16)),
+            "octal" => Some(Radix(8)),
+            "binary" => Some(Radix(2)),
+            "base" if digits >= 2 && digits <= 36 => Some(Radix(digits)),
+            "auto" => Some(Auto),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Show for DisplayMode {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Fractions => write!(fmt, "fraction"),
+            Mixed => write!(fmt, "mixed"),
+            Decimal(n) => write!(fmt, "decimal {}", n),
+            Scientific(n) => write!(fmt, "scientific {}", n),
+            Engineering(n) => write!(fmt, "engineering {}", n),
+            Radix(16) => write!(fmt, "hex"),
+            Radix(8) => write!(fmt, "octal"),
+            Radix(2) => write!(fmt, "binary"),
+            Radix(n) => write!(fmt, "base {}", n),
+            Auto => write!(fmt, "auto"),
+        }
+    }
+}
+
+/// The random number generator behind `random' and friends. Every frame of an
+/// environment shares the one generator, as do the copies `main' evaluates
+/// each line in, so the sequence after a `seed' is always the same.
+pub type SharedRng = Arc<Mutex<XorShiftRng>>;
+
+/// One level of scope: the names bound in it, and the scope it is inside of.
+pub struct Frame {
+    symbols: HashMap<String, LiteralType>,
+    constants: HashSet<String>,
+    parent: Option<SharedFrame>,
+}
+
+/// Frames are shared rather than copied, by the environments of the calls
+/// made in them and by the procedures that close over them. They sit behind a
+/// mutex rather than a `RefCell' so that `main' can hand the environment to
+/// the task it evaluates each line in.
+pub type SharedFrame = Arc<Mutex<Frame>>;
+
+fn new_shared_frame(parent: Option<SharedFrame>) -> SharedFrame {
+    Arc::new(Mutex::new(Frame { symbols: HashMap::new(), constants: HashSet::new(),
+                                parent: parent }))
+}
+
+/// Where expressions are evaluated. Copying an environment is cheap, and the
+/// copy sees the same variables as the original.
 #[deriving(Clone)]
 pub struct Environment {
-    pub symbols: HashMap<String, LiteralType>,
-    pub parent: Option<Box<Environment>>
+    pub frame: SharedFrame,
+    pub precision: uint,
+    pub display: DisplayMode,
+    pub rng: SharedRng
 }
 
 impl Environment {
     pub fn new_global() -> Environment {
-        Environment { symbols:  HashMap::new(), parent: None }
+        Environment { frame: new_shared_frame(None), precision: DEFAULT_PRECISION,
+                      display: Auto, rng: Arc::new(Mutex::new(rand::weak_rng())) }
+    }
+
+    /// An empty frame inside of the current one.
+    pub fn new_frame(par: &Environment) -> Environment {
+        Environment { frame: new_shared_frame(Some(par.frame.clone())),
+                      precision: par.precision, display: par.display, rng: par.rng.clone() }
+    }
+
+    /// A frame for calling a procedure made in `scope'. Its variables are the
+    /// ones the procedure closed over, but the precision and display are the
+    /// caller's.
+    pub fn new_call_frame(&self, scope: &Scope) -> Environment {
+        let parent = match *scope {
+            Captured(ref frame) => frame.clone(),
+            Enclosing(ref frame) => match frame.upgrade() {
+                Some(x) => x,
+                None => fail!("A procedure outlived the frame it was bound in")
+            },
+            Global => self.global()
+        };
+        Environment { frame: new_shared_frame(Some(parent)), precision: self.precision,
+                      display: self.display, rng: self.rng.clone() }
     }
 
-    pub fn new_frame(par: &mut Environment) -> Environment {
-        Environment { symbols: HashMap::new(), parent: Some(box par.clone()) }
+    /// The outermost frame, where top level definitions live.
+    pub fn global(&self) -> SharedFrame {
+        let mut frame = self.frame.clone();
+        loop {
+            let parent = match frame.lock().parent {
+                Some(ref par) => par.clone(),
+                None => break
+            };
+            frame = parent;
+        }
+        frame
+    }
+
+    /// Is this the outermost frame?
+    pub fn is_global(&self) -> bool {
+        self.frame.lock().parent.is_none()
+    }
+
+    /// Binds a name in the current frame, hiding any binding further out.
+    pub fn bind(&mut self, var: String, val: LiteralType) {
+        let val = weaken(&self.frame, val);
+        self.frame.lock().symbols.insert(var, val);
+    }
+
+    /// Binds a name in the current frame as a constant.
+    pub fn bind_constant(&mut self, var: String, val: LiteralType) {
+        let mut frame = self.frame.lock();
+        frame.symbols.insert(var.clone(), val);
+        frame.constants.insert(var);
     }
 
+    /// Changes the value of the nearest binding of a name, in whichever frame
+    /// it is in, and says whether it did. A constant there is left alone. It
+    /// is an error for the name not to be bound.
+    pub fn set(&mut self, var: &String, val: LiteralType) -> CalcResult<bool> {
+        let mut frame = self.frame.clone();
+        loop {
+            let parent = {
+                let mut guard = frame.lock();
+                if guard.constants.contains(var) {
+                    return Ok(false)
+                }
+                if guard.symbols.contains_key(var) {
+                    guard.symbols.insert(var.clone(), weaken(&frame, val));
+                    return Ok(true)
+                }
+                match guard.parent {
+                    Some(ref par) => par.clone(),
+                    None => return Err(UnboundArg(var.clone()))
+                }
+            };
+            frame = parent;
+        }
+    }
+
+    /// Lets `define' replace a constant of the current frame from now on.
+    pub fn unmark_constant(&mut self, var: &String) {
+        self.frame.lock().constants.remove(var);
+    }
+
+    /// Is this name bound to a constant in the current frame, which `define'
+    /// may not replace? A constant further out can be hidden by a new binding.
+    pub fn is_constant(&self, var: &String) -> bool {
+        self.frame.lock().constants.contains(var)
+    }
+
+    /// The value of the nearest binding of a name. Each frame is only locked
+    /// while it is searched.
     pub fn lookup(&self, var: &String) -> CalcResult<LiteralType> {
-        match self.symbols.find(var) {
-            Some(val) => Ok(val.clone()),
-            None      => {
-                if self.parent.is_some() {
-                    self.parent.clone().unwrap().lookup(var)
-                } else {
-                    Err(UnboundArg(var.clone()))
+        let mut frame = self.frame.clone();
+        loop {
+            let parent = {
+                let guard = frame.lock();
+                match guard.symbols.find(var) {
+                    Some(val) => return Ok(strengthen(&frame, val)),
+                    None => { }
+                }
+                match guard.parent {
+                    Some(ref par) => par.clone(),
+                    None => return Err(UnboundArg(var.clone()))
                 }
-            }
+            };
+            frame = parent;
+        }
+    }
+}
+
+/// The frame a procedure was made in, which its body sees when it is called.
+/// Procedures made at the top level find the global frame when they are
+/// called rather than holding on to it, since the global frame holds them and
+/// the two would otherwise keep each other alive. For the same reason, one
+/// bound in the very frame it was made in, as by `letrec' or a named `let',
+/// only holds that frame weakly while it is stored there.
+#[deriving(Clone)]
+pub enum Scope {
+    Global,
+    Captured(SharedFrame),
+    Enclosing(Weak<Mutex<Frame>>),
+}
+
+impl Scope {
+    pub fn of(env: &Environment) -> Scope {
+        if env.is_global() {
+            Global
+        } else {
+            Captured(env.frame.clone())
         }
     }
 }
 
+fn same_frame(a: &SharedFrame, b: &SharedFrame) -> bool {
+    &**a as *const Mutex<Frame> == &**b as *const Mutex<Frame>
+}
+
+/// The value to store in `frame', where a procedure that closes over that
+/// frame refers to it weakly.
+fn weaken(frame: &SharedFrame, val: LiteralType) -> LiteralType {
+    let encloses = match val {
+        Proc(_, _, Captured(ref scope)) => same_frame(scope, frame),
+        _ => false
+    };
+    if !encloses {
+        return val
+    }
+    match val {
+        Proc(vars, body, _) => Proc(vars, body, Enclosing(frame.downgrade())),
+        x => x
+    }
+}
+
+/// A value taken out of `frame', which holds on to the frame it closes over
+/// again, since it may outlive the binding it came from.
+fn strengthen(frame: &SharedFrame, val: &LiteralType) -> LiteralType {
+    match *val {
+        Proc(ref vars, ref body, Enclosing(_)) => {
+            Proc(vars.clone(), body.clone(), Captured(frame.clone()))
+        },
+        ref x => x.clone()
+    }
+}
+
+// two procedures are the same when their parameters and bodies are, wherever
+// they were made
+impl PartialEq for Scope {
+    fn eq(&self, _: &Scope) -> bool {
+        true
+    }
+}
+
+impl PartialOrd for Scope {
+    fn partial_cmp(&self, _: &Scope) -> Option<Ordering> {
+        Some(Equal)
+    }
+}
+
 impl fmt::Show for Environment {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        try!(writeln!(fmt, "{}", self.symbols));
-        try!(write!(fmt, "Has {} parent.", if self.parent.is_some() { "a" } else { "no" }));
+        let frame = self.frame.lock();
+        try!(writeln!(fmt, "{}", frame.symbols));
+        try!(write!(fmt, "Has {} parent.", if frame.parent.is_some() { "a" } else { "no" }));
         Ok(())
     }
 }
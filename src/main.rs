@@ -6,7 +6,7 @@
 #[cfg(not(test))]
 extern crate types;
 #[cfg(not(test))]
-use types::Environment;
+use types::{Environment, DisplayMode, DEFAULT_DISPLAY_DIGITS};
 
 #[cfg(target_os = "linux" , not(test))]
 use r_readline::*;
@@ -22,7 +22,12 @@ pub use calc::eval;
 #[cfg(not(test))]
 use calc::pretty::pretty_print;
 
+#[cfg(not(test))]
+use calc::constants::prelude;
+
 use std::task;
+#[cfg(not(test))]
+use std::os;
 
 #[cfg(test)]
 mod test;
@@ -96,12 +101,45 @@ pub mod rust_no_readline {
     }
 }
 
+/// Reads the command line flags. `--display MODE [DIGITS]' sets how results
+/// are written out, just like `set-display'.
+#[cfg(not(test))]
+fn read_flags(env: &mut Environment) {
+    let args = os::args();
+    let mut i = 1;
+
+    while i < args.len() {
+        match args[i].as_slice() {
+            "-d" | "--display" if i + 1 < args.len() => {
+                let digits = if i + 2 < args.len() {
+                    from_str::<uint>(args[i + 2].as_slice())
+                } else {
+                    None
+                };
+
+                match DisplayMode::from_name(args[i + 1].as_slice(),
+                                             digits.unwrap_or(DEFAULT_DISPLAY_DIGITS)) {
+                    Some(mode) => env.display = mode,
+                    None => println!("Unknown display mode `{}'", args[i + 1])
+                }
+                i += if digits.is_some() { 3 } else { 2 };
+            },
+            x => {
+                println!("Unrecognized option `{}'", x);
+                i += 1;
+            }
+        }
+    }
+}
+
 #[cfg(not(test))]
 fn main() {
     //env will hold all user defined variables and functions in hashmaps,
     //to be looked up when called. They're in the main function for
     //persistence.
     let mut env = Environment::new_global();
+    prelude(&mut env);
+    read_flags(&mut env);
 
     loop {
         let expr = match rust_readline(">>> ") {
@@ -125,6 +163,9 @@ fn main() {
 
         let expr = expr.as_slice().trim().to_string();
         let (tx, rx) = channel();
+        // the copy shares its frames with env, so a line that fails keeps the
+        // definitions it made before failing. No frame is locked while user
+        // code runs, so a failure can't leave one poisoned.
         tx.send(env.clone());
         
         let ok = task::try(proc() {
@@ -0,0 +1,53 @@
+//! Benchmarks for calling user defined procedures, which make a new frame on
+//! every call. Run them with `make bench`.
+
+extern crate test;
+extern crate types;
+
+use self::test::Bencher;
+use self::types::Environment;
+
+use super::super::eval;
+
+/// An environment with a few hundred global variables, like one that has been
+/// used for a while.
+fn busy_env() -> Environment {
+    let mut env = Environment::new_global();
+    for i in range(0u, 300) {
+        eval(format!("(define var{} {})", i, i).as_slice(), &mut env).unwrap();
+    }
+    env
+}
+
+#[bench]
+fn recursive_calls(b: &mut Bencher) {
+    let mut env = Environment::new_global();
+    eval("(define (slow-fib n) (if (< n 2) n (+ (slow-fib (- n 1)) (slow-fib (- n 2)))))",
+         &mut env).unwrap();
+    b.iter(|| eval("(slow-fib 15)", &mut env));
+}
+
+#[bench]
+fn calls_in_busy_env(b: &mut Bencher) {
+    let mut env = busy_env();
+    eval("(define (square x) (* x x))", &mut env).unwrap();
+    b.iter(|| eval("(map square '(1 2 3 4 5 6 7 8 9 10))", &mut env));
+}
+
+#[bench]
+fn closure_calls(b: &mut Bencher) {
+    let mut env = busy_env();
+    eval("(define (make-adder n) (lambda (x) (+ x n)))", &mut env).unwrap();
+    eval("(define add5 (make-adder 5))", &mut env).unwrap();
+    b.iter(|| eval("(add5 3)", &mut env));
+}
+
+/// What `main' does for each line typed in.
+#[bench]
+fn repl_line(b: &mut Bencher) {
+    let env = busy_env();
+    b.iter(|| {
+        let mut copy = env.clone();
+        eval("(+ var1 var2)", &mut copy)
+    });
+}
@@ -5,10 +5,12 @@ extern crate types;
 use std::num;
 use self::types::{/*CalcResult, ErrorKind, */ Environment, BadNumberOfArgs};
 use self::types::sexpr::{Atom, /*SExpr*/};
-use self::types::literal::{LiteralType};
+use self::types::literal::{LiteralType, Boolean, Symbol, Void};
 
 use super::eval;
 
+mod bench;
+
 #[test]
 fn arith_test() {
     let zero: LiteralType = num::zero();
@@ -23,3 +25,492 @@ fn arith_test() {
     assert_eq!(eval("(pow 4 1/2)", &mut env), Ok(Atom(two.clone())));
     assert_eq!(eval("(sin 0)", &mut env), Ok(Atom(zero.clone())));
 }
+
+#[test]
+fn transcendental_test() {
+    let zero: LiteralType = num::zero();
+    let one: LiteralType = num::one();
+
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(cos 0)", &mut env), Ok(Atom(one.clone())));
+    assert_eq!(eval("(exp 0)", &mut env), Ok(Atom(one.clone())));
+    assert_eq!(eval("(ln 1)", &mut env), Ok(Atom(zero.clone())));
+
+    assert_eq!(eval("(set-precision 5)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(exp 1)", &mut env), eval("(/ 271828 100000)", &mut env));
+    assert_eq!(eval("(atan 1)", &mut env), eval("(/ 78540 100000)", &mut env));
+}
+
+#[test]
+fn power_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(pow 256 1/8)", &mut env), eval("(+ 2)", &mut env));
+    assert_eq!(eval("(pow 27/8 2/3)", &mut env), eval("(/ 9 4)", &mut env));
+    assert_eq!(eval("(pow 8 -2/3)", &mut env), eval("(/ 1 4)", &mut env));
+    assert_eq!(eval("(pow -8 1/3)", &mut env), eval("(- 2)", &mut env));
+    assert_eq!(eval("(pow -1 1000000000000000000001/1)", &mut env), eval("(- 1)", &mut env));
+
+    assert_eq!(eval("(set-precision 5)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(pow 2 1/2)", &mut env), eval("(/ 141421 100000)", &mut env));
+}
+
+#[test]
+fn constants_test() {
+    use super::calc::constants::prelude;
+
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(set-precision 5)", &mut env), Ok(Atom(Void)));
+    prelude(&mut env);
+
+    assert_eq!(eval("(sin pi)", &mut env), eval("(+ 0)", &mut env));
+    assert_eq!(eval("(+ pi)", &mut env), eval("(/ 314159 100000)", &mut env));
+    assert_eq!(eval("(+ euler-gamma)", &mut env), eval("(/ 57722 100000)", &mut env));
+    assert!(eval("(define pi 3)", &mut env).is_err());
+    assert!(eval("(set! pi 3)", &mut env).is_err());
+    assert_eq!(eval("(let ((pi 3)) (set! pi 4) pi)", &mut env), eval("(+ 4)", &mut env));
+    // a define inside a body only hides the constant
+    assert_eq!(eval("(let ((pi 3)) (define pi 4) pi)", &mut env), eval("(+ 4)", &mut env));
+    assert_eq!(eval("(define (f) (define e 2) e)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(f)", &mut env), eval("(+ 2)", &mut env));
+    assert!(eval("(define e 2)", &mut env).is_err());
+    assert_eq!(eval("(redefine pi 3)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(+ pi)", &mut env), eval("(+ 3)", &mut env));
+}
+
+#[test]
+fn complex_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(* 3+4i 3-4i)", &mut env), eval("(+ 25)", &mut env));
+    assert_eq!(eval("(+ 1+2i -1-2i)", &mut env), eval("(+ 0)", &mut env));
+    assert_eq!(eval("(/ 1i)", &mut env), eval("(+ -1i)", &mut env));
+    assert_eq!(eval("(pow -4 1/2)", &mut env), eval("(+ 2i)", &mut env));
+    assert_eq!(eval("(pow 1+1i 2)", &mut env), eval("(+ 2i)", &mut env));
+    assert_eq!(eval("(abs 3+4i)", &mut env), eval("(+ 5)", &mut env));
+    assert_eq!(eval("(conj 3+4i)", &mut env), eval("(+ 3-4i)", &mut env));
+    assert_eq!(eval("(= 1i 1i)", &mut env), eval("(= 2 2)", &mut env));
+    assert!(eval("(< 1i 2)", &mut env).is_err());
+    assert_eq!(eval("(im 3+4i)", &mut env), eval("(+ 4)", &mut env));
+
+    assert_eq!(eval("(set-precision 5)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(ln -1)", &mut env), eval("(* 314159/100000 1i)", &mut env));
+}
+
+#[test]
+fn exactness_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(+ 0.1 0.2)", &mut env), eval("(/ 3 10)", &mut env));
+    assert_eq!(eval("(* 2 #i0.25)", &mut env), eval("(+ #i0.5)", &mut env));
+    assert_eq!(eval("(exact? #i0.5)", &mut env), Ok(Atom(Boolean(false))));
+    assert_eq!(eval("(inexact->exact #i0.5)", &mut env), eval("(/ 2)", &mut env));
+    assert_eq!(eval("(exact->inexact 1/4)", &mut env), eval("(+ #i0.25)", &mut env));
+    assert_eq!(eval("(< 1/3 #i0.5)", &mut env), Ok(Atom(Boolean(true))));
+    assert!(eval("(+ 1i #i0.5)", &mut env).is_err());
+    assert!(eval("(* #i0.5 (poly '(2 4)))", &mut env).is_err());
+    assert!(eval("(exact->inexact 1/4+1/3i)", &mut env).is_err());
+    assert_eq!(eval("(inexact->exact (exact->inexact (/ (pow 2 1020))))", &mut env),
+               eval("(/ (pow 2 1020))", &mut env));
+}
+
+#[test]
+fn display_test() {
+    use self::types::{DisplayMode, Fractions, Mixed, Decimal, Scientific, Engineering, Auto,
+                      Radix};
+    use self::types::Ratio;
+    use self::types::bigint::ToBigInt;
+    use super::calc::pretty::format_number;
+
+    let show = |n: int, d: int, mode: DisplayMode| {
+        format_number(&Ratio::new(n.to_bigint().unwrap(), d.to_bigint().unwrap()), mode, 30)
+    };
+
+    assert_eq!(show(22, 7, Fractions), "22/7".to_string());
+    assert_eq!(show(-22, 7, Mixed), "-3 1/7".to_string());
+    assert_eq!(show(1, 7, Decimal(6)), "0.(142857)".to_string());
+    assert_eq!(show(1, 7, Decimal(3)), "0.143...".to_string());
+    assert_eq!(show(-1, 6, Auto), "-0.1(6)".to_string());
+    assert_eq!(show(1, 97, Auto), "1/97".to_string());
+    assert_eq!(show(99999, 1, Scientific(2)), "1.00e5".to_string());
+    assert_eq!(show(12345, 1, Engineering(1)), "12.3e3".to_string());
+    assert_eq!(show(1, 2000, Engineering(0)), "500e-6".to_string());
+
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(set-display 'decimal 6)", &mut env), Ok(Atom(Void)));
+    assert!(env.display == Decimal(6));
+    assert_eq!(eval("(set-display 'binary)", &mut env), Ok(Atom(Void)));
+    assert!(env.display == Radix(2));
+    assert!(eval("(set-display 'bogus)", &mut env).is_err());
+}
+
+#[test]
+fn radix_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(+ 0xff)", &mut env), eval("(+ 255)", &mut env));
+    assert_eq!(eval("(+ 0o17 0b101)", &mut env), eval("(+ 20)", &mut env));
+    assert_eq!(eval("(+ 36#zz)", &mut env), eval("(+ 1295)", &mut env));
+    assert_eq!(eval("(+ -0x10)", &mut env), eval("(+ -16)", &mut env));
+    assert_eq!(eval("(+ 6.022e23)", &mut env), eval("(* 6022 (pow 10 20))", &mut env));
+    assert_eq!(eval("(+ 1e-9)", &mut env), eval("(/ (pow 10 9))", &mut env));
+    assert_eq!(eval("(+ 2e3+1i)", &mut env), eval("(+ 2000 1i)", &mut env));
+    assert_eq!(eval("(to-base 255 16)", &mut env), Ok(Atom(Symbol("0xff".to_string()))));
+    assert_eq!(eval("(to-base 1295 36)", &mut env), Ok(Atom(Symbol("36#zz".to_string()))));
+    assert!(eval("(to-base 1/2 2)", &mut env).is_err());
+}
+
+#[test]
+fn number_theory_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(gcd 12 18 30)", &mut env), eval("(+ 6)", &mut env));
+    assert_eq!(eval("(lcm 4 6)", &mut env), eval("(+ 12)", &mut env));
+    assert_eq!(eval("(lcm 0 0)", &mut env), eval("(+ 0)", &mut env));
+    assert_eq!(eval("(lcm 3 0 5)", &mut env), eval("(+ 0)", &mut env));
+    assert_eq!(eval("(mod-pow 4 13 497)", &mut env), eval("(+ 445)", &mut env));
+    assert_eq!(eval("(mod-inv 17 3120)", &mut env), eval("(+ 2753)", &mut env));
+    assert_eq!(eval("(crt '(2 3 2) '(3 5 7))", &mut env), eval("(+ 23)", &mut env));
+    assert_eq!(eval("(totient 36)", &mut env), eval("(+ 12)", &mut env));
+    assert_eq!(eval("(divisors 12)", &mut env), eval("(list 1 2 3 4 6 12)", &mut env));
+    assert_eq!(eval("(sigma 12)", &mut env), eval("(+ 28)", &mut env));
+    assert_eq!(eval("(jacobi 1001 9907)", &mut env), eval("(+ -1)", &mut env));
+    assert!(eval("(gcd 1/2 3)", &mut env).is_err());
+    assert!(eval("(mod-inv 2 4)", &mut env).is_err());
+}
+
+#[test]
+fn primes_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(prime? 97)", &mut env), Ok(Atom(Boolean(true))));
+    assert_eq!(eval("(prime? 3215031751)", &mut env), Ok(Atom(Boolean(false))));
+    assert_eq!(eval("(prime? 170141183460469231731687303715884105727)", &mut env),
+               Ok(Atom(Boolean(true))));
+    assert_eq!(eval("(prime? 170141183460469231731687303715884105729)", &mut env),
+               Ok(Atom(Boolean(false))));
+    assert_eq!(eval("(next-prime 100)", &mut env), eval("(+ 101)", &mut env));
+    assert_eq!(eval("(prev-prime 100)", &mut env), eval("(+ 97)", &mut env));
+    assert_eq!(eval("(primes-up-to 20)", &mut env), eval("(list 2 3 5 7 11 13 17 19)", &mut env));
+    assert_eq!(eval("(factor 360)", &mut env),
+               eval("(list (list 2 3) (list 3 2) (list 5 1))", &mut env));
+    assert_eq!(eval("(factor 18446744073709551617)", &mut env),
+               eval("(list (list 274177 1) (list 67280421310721 1))", &mut env));
+}
+
+#[test]
+fn combinatorics_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(factorial 20)", &mut env), eval("(+ 2432902008176640000/1)", &mut env));
+    assert_eq!(eval("(choose 52 5)", &mut env), eval("(+ 2598960)", &mut env));
+    assert_eq!(eval("(permutations 10 3)", &mut env), eval("(+ 720)", &mut env));
+    assert_eq!(eval("(multinomial 2 3 4)", &mut env), eval("(+ 1260)", &mut env));
+    assert_eq!(eval("(stirling1 5 2)", &mut env), eval("(+ 50)", &mut env));
+    assert_eq!(eval("(stirling2 5 2)", &mut env), eval("(+ 15)", &mut env));
+    assert_eq!(eval("(bell 5)", &mut env), eval("(+ 52)", &mut env));
+    assert_eq!(eval("(catalan 10)", &mut env), eval("(+ 16796)", &mut env));
+    assert_eq!(eval("(partitions 100)", &mut env), eval("(+ 190569292)", &mut env));
+    assert_eq!(eval("(fib 100)", &mut env), eval("(+ 354224848179261915075/1)", &mut env));
+    assert_eq!(eval("(fib -8)", &mut env), eval("(+ -21)", &mut env));
+    assert!(eval("(factorial -1)", &mut env).is_err());
+}
+
+#[test]
+fn stats_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(mean '(1 2 3 4))", &mut env), eval("(/ 5 2)", &mut env));
+    assert_eq!(eval("(median 3 1 4 1 5)", &mut env), eval("(+ 3)", &mut env));
+    assert_eq!(eval("(mode '(1 2 2 3))", &mut env), eval("(+ 2)", &mut env));
+    assert_eq!(eval("(variance '(2 4 4 4 5 5 7 9))", &mut env), eval("(/ 32 7)", &mut env));
+    assert_eq!(eval("(pstdev '(2 4 4 4 5 5 7 9))", &mut env), eval("(+ 2)", &mut env));
+    assert_eq!(eval("(quantile '(1 2 3 4) 1/2)", &mut env), eval("(/ 5 2)", &mut env));
+    assert_eq!(eval("(min 3 -1 2)", &mut env), eval("(+ -1)", &mut env));
+    assert_eq!(eval("(product '(1 2 3 4))", &mut env), eval("(+ 24)", &mut env));
+    assert_eq!(eval("(z-score 9 '(2 4 4 4 5 5 7 9))", &mut env), eval("(+ 2)", &mut env));
+    assert_eq!(eval("(covariance '(1 2 3) '(1 2 3))", &mut env), eval("(+ 1)", &mut env));
+    assert_eq!(eval("(correlation '(1 2 3) '(6 4 2))", &mut env), eval("(+ -1)", &mut env));
+    assert_eq!(eval("(weighted-mean '(1 2) '(3 1))", &mut env), eval("(/ 5 4)", &mut env));
+    assert!(eval("(variance '(1))", &mut env).is_err());
+}
+
+#[test]
+fn regression_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(linreg '(1 2 3) '(2 4 7))", &mut env),
+               eval("(list (list -2/3 5/2) (list 1/6 -1/3 1/6) 75/76)", &mut env));
+    assert_eq!(eval("(car (polyfit '(0 1 2 3) '(1 2 5 10) 2))", &mut env),
+               eval("(list 1 0 1)", &mut env));
+    assert_eq!(eval("(map (linreg-fn '(1 2 3) '(2 4 6)) '(10))", &mut env),
+               eval("(list 20)", &mut env));
+    assert!(eval("(polyfit '(1 1 1) '(1 2 3) 1)", &mut env).is_err());
+}
+
+#[test]
+fn distributions_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(binomial-pmf 2 4 1/2)", &mut env), eval("(/ 3 8)", &mut env));
+    assert_eq!(eval("(binomial-cdf 2 4 1/2)", &mut env), eval("(/ 11 16)", &mut env));
+    assert_eq!(eval("(binomial-quantile 1/2 4 1/2)", &mut env), eval("(+ 2)", &mut env));
+    assert_eq!(eval("(uniform-cdf 1 0 4)", &mut env), eval("(/ 1 4)", &mut env));
+    assert_eq!(eval("(normal-cdf 0 0 1)", &mut env), eval("(/ 1 2)", &mut env));
+
+    assert_eq!(eval("(set-precision 5)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(normal-cdf 1)", &mut env), eval("(+ 0.84134)", &mut env));
+    assert_eq!(eval("(normal-quantile 0.975)", &mut env), eval("(+ 1.95996)", &mut env));
+    assert_eq!(eval("(student-t-cdf 1 1)", &mut env), eval("(/ 3 4)", &mut env));
+    assert_eq!(eval("(chi-squared-cdf 2 2)", &mut env), eval("(+ 0.63212)", &mut env));
+    assert_eq!(eval("(poisson-pmf 0 1)", &mut env), eval("(+ 0.36788)", &mut env));
+    assert!(eval("(normal-quantile 1)", &mut env).is_err());
+    let near_one = format!("0.{}", "9".repeat(40));
+    assert!(eval(format!("(poisson-quantile {} 1)", near_one).as_slice(), &mut env).is_ok());
+    assert!(eval("(binomial-pmf 1 100000000000000000000000 1/2)", &mut env).is_err());
+    assert!(eval("(poisson-pmf 1 100000000000000000000000)", &mut env).is_err());
+    assert!(eval("(poisson-cdf 1000000000000 1)", &mut env).is_ok());
+}
+
+#[test]
+fn random_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(seed 42)", &mut env), Ok(Atom(Void)));
+    let first = eval("(list (random-int 1 1000000) (random) (normal-sample))", &mut env);
+    assert_eq!(eval("(seed 42)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(list (random-int 1 1000000) (random) (normal-sample))", &mut env), first);
+
+    // copies of the environment draw from the same sequence
+    let mut copy = env.clone();
+    assert_eq!(eval("(seed 7)", &mut env), Ok(Atom(Void)));
+    let (a, b) = (eval("(random)", &mut copy), eval("(random)", &mut env));
+    assert_eq!(eval("(seed 7)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(random)", &mut env), a);
+    assert_eq!(eval("(random)", &mut env), b);
+
+    assert_eq!(eval("(sort (shuffle '(3 1 2)))", &mut env), eval("(list 1 2 3)", &mut env));
+    assert_eq!(eval("(random-int 5 5)", &mut env), eval("(+ 5)", &mut env));
+    assert_eq!(eval("(random-rational 1/3 1/2 3)", &mut env), eval("(/ 1 3)", &mut env));
+    assert!(eval("(sample '(1 2) 3)", &mut env).is_err());
+}
+
+#[test]
+fn integrate_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(set-precision 10)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(integrate (lambda (x) (* x x)) 0 1)", &mut env),
+               eval("(+ 0.3333333333)", &mut env));
+    assert_eq!(eval("(integrate (lambda (x) (* x x)) 1 0)", &mut env),
+               eval("(- 0.3333333333)", &mut env));
+    assert_eq!(eval("(integrate (lambda (x) (/ 1 (+ 1 (* x x)))) #i-inf #iinf)", &mut env),
+               eval("(+ 3.1415926536)", &mut env));
+    assert_eq!(eval("(integrate (lambda (x) x) 2 2)", &mut env), eval("(+ 0)", &mut env));
+    assert!(eval("(integrate (lambda (x y) x) 0 1)", &mut env).is_err());
+}
+
+#[test]
+fn derivative_limit_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(set-precision 10)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(derivative (lambda (x) (* x x x)) 2)", &mut env), eval("(+ 12)", &mut env));
+    assert_eq!(eval("(derivative (lambda (x) (* x x x)) 2 2)", &mut env),
+               eval("(+ 12)", &mut env));
+    assert_eq!(eval("(limit (lambda (x) (/ (- (* x x) 1) (- x 1))) 1)", &mut env),
+               eval("(+ 2)", &mut env));
+    assert_eq!(eval("(limit (lambda (x) (/ x (+ x 1))) #iinf)", &mut env), eval("(+ 1)", &mut env));
+    assert_eq!(eval("(limit (lambda (x) (/ (abs x) x)) 0 'right)", &mut env),
+               eval("(+ 1)", &mut env));
+    assert!(eval("(limit (lambda (x) (/ (abs x) x)) 0)", &mut env).is_err());
+    assert!(eval("(limit (lambda (x) (/ 1 x)) 0 'right)", &mut env).is_err());
+}
+
+#[test]
+fn solve_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(set-precision 10)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(find-root (lambda (x) (- (* x x) 4)) 0 3)", &mut env),
+               eval("(+ 2)", &mut env));
+    assert_eq!(eval("(find-root (lambda (x) (- (* x x) 2)) 0 2)", &mut env),
+               eval("(+ 1.4142135624)", &mut env));
+    assert!(eval("(find-root (lambda (x) (- (* x x) 4)) 3 4)", &mut env).is_err());
+    assert_eq!(eval("(newton (lambda (x) (- (* x x) 4)) 1)", &mut env), eval("(+ 2)", &mut env));
+    assert!(eval("(newton (lambda (x) (+ (* x x) 1)) 0)", &mut env).is_err());
+    assert_eq!(eval("(minimize (lambda (x) (* (- x 1) (- x 1))) 0 3 1e-14)", &mut env),
+               eval("(+ 1)", &mut env));
+}
+
+#[test]
+fn series_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(sum (lambda (k) (* k k)) 1 10)", &mut env), eval("(+ 385)", &mut env));
+    assert_eq!(eval("(prod (lambda (k) k) 1 5)", &mut env), eval("(+ 120)", &mut env));
+    assert_eq!(eval("(sum (lambda (k) k) 5 1)", &mut env), eval("(+ 0)", &mut env));
+    assert_eq!(eval("(prod 2 3 4)", &mut env), eval("(+ 24)", &mut env));
+
+    assert_eq!(eval("(set-precision 10)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(series (lambda (k) (/ 1 (pow 2 k))) 0)", &mut env), eval("(+ 2)", &mut env));
+    assert_eq!(eval("(series (lambda (k) (/ (pow -1 k) (+ (* 2 k) 1))) 0)", &mut env),
+               eval("(+ 0.7853981634)", &mut env));
+    assert!(eval("(series (lambda (k) (/ 1 k)) 1)", &mut env).is_err());
+}
+
+#[test]
+fn polynomial_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(* (poly '(1 1)) (poly '(-1 1)))", &mut env),
+               eval("(poly '(-1 0 1))", &mut env));
+    assert_eq!(eval("(- (poly '(1 1)) (poly '(0 1)))", &mut env), eval("(+ 1)", &mut env));
+    assert_eq!(eval("(poly-div (poly '(-1 0 1)) (poly '(-1 1)))", &mut env),
+               eval("(list (poly '(1 1)) 0)", &mut env));
+    assert_eq!(eval("(poly-eval (poly '(-1 2 3)) 2)", &mut env), eval("(+ 15)", &mut env));
+    assert_eq!(eval("(poly-gcd (poly '(-1 0 1)) (poly '(1 2 1)))", &mut env),
+               eval("(poly '(1 1))", &mut env));
+    assert_eq!(eval("(poly-deriv (poly '(-1 2 3)))", &mut env), eval("(poly '(2 6))", &mut env));
+    assert_eq!(eval("(poly-roots (poly '(-1 2 3)))", &mut env), eval("(list -1 1/3)", &mut env));
+    assert_eq!(eval("(matrix-char-poly (make-matrix (list (list 2 1) (list 1 2))))", &mut env),
+               eval("(poly '(3 -4 1))", &mut env));
+    assert_eq!(eval("(% (poly '(1 1)) 2)", &mut env), eval("(+ 0)", &mut env));
+    assert_eq!(eval("(% 2 (poly '(1 1)))", &mut env), eval("(+ 2)", &mut env));
+    assert!(eval("(/ 1 (poly '(0 1)))", &mut env).is_err());
+    assert!(eval("(% (poly '(1 1)) 0)", &mut env).is_err());
+
+    let shown = match eval("(poly '(-1 -2 3/2))", &mut env) {
+        Ok(Atom(p)) => p.to_string(),
+        _ => "".to_string()
+    };
+    assert_eq!(shown, "(3/2)x^2 - 2x - 1".to_string());
+
+    assert_eq!(eval("(set-precision 10)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(poly-roots (poly '(-2 0 1)))", &mut env),
+               eval("(list -1.4142135624 1.4142135624)", &mut env));
+}
+
+#[test]
+fn symbolic_derivative_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(d/dx (lambda (x) (+ (* 3 x) 1)) 'x)", &mut env),
+               eval("(lambda (x) (+ 3))", &mut env));
+    assert_eq!(eval("(d/dx (lambda (x) (pow x 3)))", &mut env),
+               eval("(lambda (x) (* 3 (pow x 2)))", &mut env));
+    assert_eq!(eval("(d/dx (lambda (x y) (* x y)) 'y)", &mut env),
+               eval("(lambda (x y) (+ x))", &mut env));
+    assert_eq!(eval("(d/dx (lambda (x) (* x (sin x))))", &mut env),
+               eval("(lambda (x) (+ (sin x) (* x (cos x))))", &mut env));
+
+    assert_eq!(eval("(define g (d/dx (lambda (x) (* x x x)) 'x))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(g 2)", &mut env), eval("(+ 12)", &mut env));
+    assert!(eval("(d/dx (lambda (x) (if (> x 0) x 0)))", &mut env).is_err());
+}
+
+#[test]
+fn closure_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(define (make-adder n) (lambda (x) (+ x n)))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(define add5 (make-adder 5))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(add5 3)", &mut env), eval("(+ 8)", &mut env));
+    assert_eq!(eval("(map add5 '(1 2))", &mut env), eval("'(6 7)", &mut env));
+
+    assert_eq!(eval("(define (curry a) (lambda (b) (lambda (c) (* a b c))))", &mut env),
+               Ok(Atom(Void)));
+    assert_eq!(eval("(define times2 (curry 2))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(define times6 (times2 3))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(times6 4)", &mut env), eval("(+ 24)", &mut env));
+
+    // a procedure sees the variables where it was made, not where it is called
+    assert_eq!(eval("(define y 1)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(define (f z) (+ y z))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(define (g y) (f 0))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(g 10)", &mut env), eval("(+ 1)", &mut env));
+
+    // variables passed along are looked up where the call is made
+    assert_eq!(eval("(define (sq x) (* x x))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(define (sq-of a) (sq a))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(sq-of 3)", &mut env), eval("(+ 9)", &mut env));
+    assert_eq!(eval("(define (twice k v) (k (k v)))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(twice sq 3)", &mut env), eval("(+ 81)", &mut env));
+}
+
+#[test]
+fn shared_frame_test() {
+    let mut env = Environment::new_global();
+    let mut copy = env.clone();
+    assert_eq!(eval("(define z 3)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(+ z 1)", &mut copy), eval("(+ 4)", &mut env));
+
+    // a call's parameters go in a frame of its own
+    assert_eq!(eval("(define (h z) (* z 2))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(h 10)", &mut env), eval("(+ 20)", &mut env));
+    assert_eq!(eval("(+ z)", &mut env), eval("(+ 3)", &mut env));
+}
+
+#[test]
+fn tail_call_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(define (count n acc) (if (= n 0) acc (count (- n 1) (+ acc 1))))", &mut env),
+               Ok(Atom(Void)));
+    assert_eq!(eval("(count 100000 0)", &mut env), eval("(+ 100000)", &mut env));
+
+    assert_eq!(eval("(define (ev n) (if (= n 0) true (od (- n 1))))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(define (od n) (if (= n 0) false (ev (- n 1))))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(ev 50001)", &mut env), Ok(Atom(Boolean(false))));
+    assert!(eval("(count 1)", &mut env).is_err());
+}
+
+#[test]
+fn let_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(let ((x 2) (y 3)) (* x y))", &mut env), eval("(+ 6)", &mut env));
+    assert!(eval("(+ x)", &mut env).is_err());
+
+    assert_eq!(eval("(define x 10)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(let ((x 2) (y x)) (+ x y))", &mut env), eval("(+ 12)", &mut env));
+    assert_eq!(eval("(let* ((x 2) (y x)) (+ x y))", &mut env), eval("(+ 4)", &mut env));
+    assert_eq!(eval("(+ x)", &mut env), eval("(+ 10)", &mut env));
+
+    let letrec = format!("(letrec ({} {}) (ev 11))",
+                         "(ev (lambda (n) (if (= n 0) true (od (- n 1)))))",
+                         "(od (lambda (n) (if (= n 0) false (ev (- n 1)))))");
+    assert_eq!(eval(letrec.as_slice(), &mut env), Ok(Atom(Boolean(false))));
+
+    // a procedure bound by letrec still works once it is out of the letrec
+    let letrec = format!("(define even (letrec ({} {}) ev))",
+                         "(ev (lambda (n) (if (= n 0) true (od (- n 1)))))",
+                         "(od (lambda (n) (if (= n 0) false (ev (- n 1)))))");
+    assert_eq!(eval(letrec.as_slice(), &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(even 10)", &mut env), Ok(Atom(Boolean(true))));
+
+    assert_eq!(eval("(let loop ((i 5) (acc 1)) (if (= i 0) acc (loop (- i 1) (* acc i))))",
+                    &mut env),
+               eval("(+ 120)", &mut env));
+    assert_eq!(eval("(let loop ((i 0)) (if (= i 100000) i (loop (+ i 1))))", &mut env),
+               eval("(+ 100000)", &mut env));
+    assert!(eval("(+ (loop 1))", &mut env).is_err());
+
+    assert_eq!(eval("(define (f n) (let ((m (* n 2))) (+ m 1)))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(f 4)", &mut env), eval("(+ 9)", &mut env));
+    assert!(eval("(let ((list 1)) list)", &mut env).is_err());
+}
+
+#[test]
+fn begin_set_test() {
+    let mut env = Environment::new_global();
+    assert_eq!(eval("(begin)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(begin (define a 1) (+ a 1))", &mut env), eval("(+ 2)", &mut env));
+    assert_eq!(eval("(set! a 5)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(+ a)", &mut env), eval("(+ 5)", &mut env));
+    assert!(eval("(set! nowhere 1)", &mut env).is_err());
+    assert!(eval("(+ nowhere)", &mut env).is_err());
+
+    // set! changes the binding where it is, not a copy of it
+    let counter = "(lambda () (let ((n 0)) (lambda () (set! n (+ n 1)) n)))";
+    assert_eq!(eval(format!("(define make-counter {})", counter).as_slice(), &mut env),
+               Ok(Atom(Void)));
+    assert_eq!(eval("(define c1 (make-counter))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(define c2 (make-counter))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(c1)", &mut env), eval("(+ 1)", &mut env));
+    assert_eq!(eval("(c1)", &mut env), eval("(+ 2)", &mut env));
+    assert_eq!(eval("(c2)", &mut env), eval("(+ 1)", &mut env));
+
+    assert_eq!(eval("(define (add-to-a x) (set! a (+ a x)) a)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(add-to-a 3)", &mut env), eval("(+ 8)", &mut env));
+    assert_eq!(eval("(let ((x 1)) (set! x 10) (* x 2))", &mut env), eval("(+ 20)", &mut env));
+    assert_eq!(eval("(define id (lambda (x) x))", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(id 4)", &mut env), eval("(+ 4)", &mut env));
+
+    // a procedure with no parameters runs its body each time it is called
+    assert_eq!(eval("(define n 0)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(define (tick) (set! n (+ n 1)) n)", &mut env), Ok(Atom(Void)));
+    assert_eq!(eval("(+ n)", &mut env), eval("(+ 0)", &mut env));
+    assert_eq!(eval("(tick)", &mut env), eval("(+ 1)", &mut env));
+    assert_eq!(eval("(tick)", &mut env), eval("(+ 2)", &mut env));
+}